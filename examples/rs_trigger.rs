@@ -5,6 +5,7 @@
 
 #![deny(warnings)]
 #![allow(dead_code)]
+#![allow(clippy::new_ret_no_self)]
 
 mod circuit {
     use components_arena::{Arena, Component, ComponentStop, Id, NewtypeComponentId, with_arena_in_state_part};
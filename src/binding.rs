@@ -1,14 +1,27 @@
 use crate::base::*;
+use crate::clock::{Clock, TimerId};
+use crate::recorder::Recorder;
 use core::alloc::Allocator;
 use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use components_arena::{ArenaItems, Component, ComponentId, Id, Arena, NewtypeComponentId, RawId};
 use composable_allocators::Global;
-use core::any::{Any, TypeId};
-use core::fmt::Debug;
-use core::mem::{MaybeUninit, align_of, size_of};
+use core::any::{Any, TypeId, type_name};
+use core::cell::Cell;
+use core::cmp::Reverse;
+use core::fmt::{self, Debug, Display, Formatter, Write};
+#[cfg(debug_assertions)]
+use core::iter::once;
+use core::mem::{MaybeUninit, align_of, replace, size_of, take};
+use core::num::NonZeroUsize;
 use core::ops::{Deref, DerefMut};
+use core::panic::Location;
 use core::ptr::{self};
-use dyn_clone::{DynClone, clone_trait_object};
+use dyn_clone::{DynClone, clone_box, clone_trait_object};
 use dyn_context::{SelfState, State, StateExt};
 use educe::Educe;
 use macro_attr_2018::macro_attr;
@@ -137,6 +150,9 @@ impl Re<!> {
 /// An object which can recieve values from a binding.
 pub trait Target<T: Convenient>: DynClone {
     fn execute(&self, state: &mut dyn State, value: T);
+
+    /// Returns a target description, used for introspection (see [`Bindings::nodes`]).
+    fn describe(&self) -> SourceDescriptor { SourceDescriptor::Other(type_name::<Self>().to_string()) }
 }
 
 clone_trait_object!(<T: Convenient> Target<T>);
@@ -144,6 +160,9 @@ clone_trait_object!(<T: Convenient> Target<T>);
 /// An object controlling binding lifetime.
 pub trait Holder {
     fn release(&self, state: &mut dyn State);
+
+    /// Returns a human-readable holder description, used for introspection (see [`Bindings::nodes`]).
+    fn describe(&self) -> String { type_name::<Self>().to_string() }
 }
 
 #[derive(Educe)]
@@ -172,6 +191,178 @@ impl<Context: Clone, T: Convenient> Target<T> for FnTarget<Context, T> {
     }
 }
 
+enum ClosureRepr<F> {
+    /// Not placed yet, see [`BindingNodeSourcesBuf::place_closure`].
+    Empty,
+    /// Stored at the offset from the `Closure` itself.
+    Inline(isize, PhantomType<F>),
+    Boxed(Box<F>),
+}
+
+/// A closure storage, used as a binding parameter.
+///
+/// A closure passed to a binding constructor is stored inline, in the binding node sources buffer
+/// space left after the binding sources, if it fits there, and boxed otherwise.
+pub struct Closure<F: 'static>(ClosureRepr<F>);
+
+impl<F: 'static> Closure<F> {
+    /// Creates a boxed closure.
+    pub fn new(f: F) -> Self {
+        Closure(ClosureRepr::Boxed(Box::new(f)))
+    }
+
+    const fn empty() -> Self {
+        Closure(ClosureRepr::Empty)
+    }
+
+    pub fn get(&self) -> &F {
+        match &self.0 {
+            ClosureRepr::Empty => unreachable!(),
+            &ClosureRepr::Inline(offset, _) => unsafe { &*((self as *const Self as *const u8).offset(offset) as *const F) },
+            ClosureRepr::Boxed(f) => f,
+        }
+    }
+}
+
+impl<F: 'static> Drop for Closure<F> {
+    fn drop(&mut self) {
+        if let &ClosureRepr::Inline(offset, _) = &self.0 {
+            unsafe { ptr::drop_in_place((self as *mut Self as *mut u8).offset(offset) as *mut F); }
+        }
+    }
+}
+
+impl<F: Clone + 'static> Clone for Closure<F> {
+    fn clone(&self) -> Self { Closure::new(self.get().clone()) }
+}
+
+impl<F: 'static> Debug for Closure<F> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("Closure")
+    }
+}
+
+#[derive(Educe)]
+#[educe(Clone(bound="F: Clone"))]
+struct ClosureTarget<F>(F);
+
+impl<T: Convenient, F: Fn(&mut dyn State, T) + Clone + 'static> Target<T> for ClosureTarget<F> {
+    fn execute(&self, state: &mut dyn State, value: T) {
+        (self.0)(state, value);
+    }
+}
+
+#[derive(Educe)]
+#[educe(Clone(bound="F: Clone"))]
+struct ClosureDispatchTarget<F>(F);
+
+impl<T: Convenient, F: Fn(&mut dyn State, T) -> Re<!> + Clone + 'static> Target<T> for ClosureDispatchTarget<F> {
+    fn execute(&self, state: &mut dyn State, value: T) {
+        let _ = (self.0)(state, value);
+    }
+}
+
+/// A [`Binding1`], [`Binding2`], ... function, getting the binding parameter by value or by reference.
+#[derive(Clone, Copy)]
+enum FilterMap<F, R> {
+    Owned(F),
+    Ref(R),
+}
+
+/// Functions called around a [`Binding1`], [`Binding2`], ... node evaluation
+/// for a parameter keeping the evaluation outcome, see [`Fallible`].
+struct EvaluationHooks<P> {
+    /// Called when the evaluation starts.
+    begin: fn(param: &P),
+    /// Called after the evaluation with the state available.
+    end: fn(state: &mut dyn State, binding: Id<AnyBindingNode>),
+}
+
+impl<P> Clone for EvaluationHooks<P> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<P> Copy for EvaluationHooks<P> { }
+
+/// A fallible binding parameter, see [`Binding1::new_fallible`], [`BindingExt1::new_fallible`], ...
+#[derive(Educe)]
+#[educe(Debug(bound="P: Debug"), Clone(bound="P: Clone, F: Clone"))]
+pub struct Fallible<P, E: Convenient, F> {
+    pub param: P,
+    #[educe(Debug(ignore))]
+    filter_map: F,
+    #[educe(Debug(ignore))]
+    error_target: Option<Box<dyn Target<Option<E>>>>,
+    failed: bool,
+    /// The last [`filter_map_ref`](Fallible::filter_map_ref) outcome, not reported yet.
+    #[educe(Debug(ignore), Clone(method="unreported"))]
+    evaluated: Cell<Option<Option<E>>>,
+}
+
+fn unreported<E>(_: &Cell<Option<Option<E>>>) -> Cell<Option<Option<E>>> { Cell::new(None) }
+
+impl<P: Clone + 'static, E: Convenient, F: Clone + 'static> Fallible<P, E, F> {
+    fn new(param: P, filter_map: F) -> Self {
+        Fallible { param, filter_map, error_target: None, failed: false, evaluated: Cell::new(None) }
+    }
+
+    /// Forgets the outcome of [`filter_map_ref`](Fallible::filter_map_ref) calls made since
+    /// the last report (e.g. by [`BindingBase::get_value`] calls from outside the binding),
+    /// so only the outcome of the starting node evaluation is reported.
+    fn begin(&self) {
+        self.evaluated.set(None);
+    }
+
+    /// Evaluates a plain binding function, which has no access to the state.
+    /// The outcome is kept until [`report`](Fallible::report) is called.
+    fn filter_map_ref<T: Convenient>(&self, filter_map: impl FnOnce(P, F) -> Result<T, E>) -> Option<T> {
+        match filter_map(self.param.clone(), self.filter_map.clone()) {
+            Ok(value) => {
+                self.evaluated.set(Some(None));
+                Some(value)
+            },
+            Err(error) => {
+                self.evaluated.set(Some(Some(error)));
+                None
+            },
+        }
+    }
+
+    /// Returns the error target and the value to send to it after
+    /// a [`filter_map_ref`](Fallible::filter_map_ref) call, if there is anything to send.
+    fn report(&mut self) -> Option<(Box<dyn Target<Option<E>>>, Option<E>)> {
+        let error = self.evaluated.take()?;
+        let failed = replace(&mut self.failed, error.is_some());
+        if error.is_none() && !failed { return None; }
+        Some((self.error_target.clone()?, error))
+    }
+
+    fn evaluate<T: Convenient>(
+        state: &mut dyn State,
+        param: Param<Self>,
+        filter_map: impl FnOnce(P, F) -> Result<T, E>
+    ) -> Re<T> {
+        let this = param.get(state);
+        let this: &Self = &this;
+        let res = filter_map(this.param.clone(), this.filter_map.clone());
+        let mut this = param.get_mut(state);
+        let failed = replace(&mut this.failed, res.is_err());
+        let error_target = this.error_target.clone();
+        match res {
+            Ok(value) => {
+                if failed {
+                    error_target.map(|x| x.execute(state, None));
+                }
+                Re::Yield(value)
+            },
+            Err(error) => {
+                error_target.map(|x| x.execute(state, Some(error)));
+                Re::Continue
+            },
+        }
+    }
+}
+
 /// Base non-generic part of the [`Handler`] trait.
 pub trait AnyHandler: Debug {
     fn clear(&self, state: &mut dyn State);
@@ -188,6 +379,11 @@ clone_trait_object!(<T> Handler<T>);
 /// A value caching strategy.
 pub trait SourceCache<T: Convenient>: Default + Debug {
     type Value: Convenient;
+
+    /// Whether a source with this caching strategy should
+    /// send its last value to a newly attached handler.
+    const KEEPS_VALUE: bool = false;
+
     fn update(&mut self, value: T);
     fn get(&self, current: Option<T>) -> Option<Self::Value>;
 }
@@ -204,6 +400,8 @@ impl<T: Convenient> Default for ValueCache<T> {
 impl<T: Convenient> SourceCache<T> for ValueCache<T> {
     type Value = T;
 
+    const KEEPS_VALUE: bool = true;
+
     fn update(&mut self, value: T) { self.0 = Some(value); }
 
     fn get(&self, current: Option<T>) -> Option<T> {
@@ -223,16 +421,177 @@ impl<T: Convenient> SourceCache<T> for NoCache {
     fn get(&self, current: Option<T>) -> Option<Option<T>> { Some(current) }
 }
 
+/// A caching strategy of a [`Source::map`] result, of the same kind as the source one.
+pub trait SourceCacheMap<T: Convenient, U: Convenient>: SourceCache<T> {
+    type Mapped: SourceCache<U>;
+}
+
+impl<T: Convenient, U: Convenient> SourceCacheMap<T, U> for ValueCache<T> {
+    type Mapped = ValueCache<U>;
+}
+
+impl<T: Convenient, U: Convenient> SourceCacheMap<T, U> for NoCache {
+    type Mapped = NoCache;
+}
+
 /// An object which can send values to a binding.
 pub trait Source: Debug {
     type Value: Convenient;
     type Cache: SourceCache<Self::Value>;
     fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<Self::Value>>) -> HandledSource;
+
+    /// Transforms values with the `f` function.
+    ///
+    /// Operator functions (here and in [`filter`](Source::filter), [`scan`](Source::scan),
+    /// and [`switch`](Source::switch)) can be plain functions or closures.
+    /// Every attached handler gets its own function copy.
+    ///
+    /// The result keeps the source caching strategy kind, see [`SourceCacheMap`].
+    fn map<U: Convenient, F: Fn(Self::Value) -> U + Clone + 'static>(self, f: F) -> Map<Self, U, F> where Self: Sized {
+        Map { source: self, f, phantom: PhantomType::new() }
+    }
+
+    /// Skips values not satisfying the `pred` predicate.
+    fn filter<F: Fn(&Self::Value) -> bool + Clone + 'static>(self, pred: F) -> Filter<Self, F> where Self: Sized {
+        Filter { source: self, pred }
+    }
+
+    /// Skips values equal to the previous one.
+    fn distinct(self) -> Distinct<Self> where Self: Sized {
+        Distinct { source: self }
+    }
+
+    /// Sends the accumulated value, computed from the previous accumulated value
+    /// (or `init`, if there is no previous one) and a source value with the `f` function.
+    ///
+    /// Every attached handler gets its own accumulator.
+    fn scan<A: Convenient, F: Fn(A, Self::Value) -> A + Clone + 'static>(
+        self,
+        init: A,
+        f: F
+    ) -> Scan<Self, A, F> where Self: Sized {
+        Scan { source: self, init, f }
+    }
+
+    /// Sends values from both sources.
+    fn merge<S: Source<Value=Self::Value, Cache=Self::Cache>>(self, other: S) -> Merge<Self, S> where Self: Sized {
+        Merge { source_1: self, source_2: other }
+    }
+
+    /// Sends values from the source returned by `f` for the last value,
+    /// e.g. a property of an object referenced by this source value.
+    ///
+    /// On every value, the previously selected source is unsubscribed.
+    /// If `f` returns `None`, the default value is sent.
+    /// Chained paths are expressed by applying `switch` to the result.
+    fn switch<I: Source + 'static, F: Fn(Self::Value) -> Option<I> + Clone + 'static>(
+        self,
+        f: F
+    ) -> Switch<Self, I, F> where Self: Sized, I::Value: Default {
+        self.switch_or(I::Value::default(), f)
+    }
+
+    /// Same as [`switch`](Source::switch), but sends `default` if `f` returns `None`.
+    fn switch_or<I: Source + 'static, F: Fn(Self::Value) -> Option<I> + Clone + 'static>(
+        self,
+        default: I::Value,
+        f: F
+    ) -> Switch<Self, I, F> where Self: Sized {
+        Switch { source: self, default, f }
+    }
+
+    /// Sends a value only after `duration` time units have passed without another value.
+    ///
+    /// Time-based operators require the [`Clock`] state part,
+    /// a binding using them panics on source handling without it.
+    fn debounce(self, duration: u64) -> Timed<Self> where Self: Sized {
+        Timed { source: self, kind: TimedKind::Debounce, duration }
+    }
+
+    /// Sends a value immediately, and skips values arriving during the next `duration` time units.
+    fn throttle(self, duration: u64) -> Timed<Self> where Self: Sized {
+        Timed { source: self, kind: TimedKind::Throttle, duration }
+    }
+
+    /// Sends every value `duration` time units later.
+    fn delay(self, duration: u64) -> Timed<Self> where Self: Sized {
+        Timed { source: self, kind: TimedKind::Delay, duration }
+    }
+
+    /// Sends the most recent value at most once per `period` time units,
+    /// at the end of the period started by the first value after the previous sending.
+    fn sample(self, period: u64) -> Timed<Self> where Self: Sized {
+        Timed { source: self, kind: TimedKind::Sample, duration: period }
+    }
 }
 
 /// An id of a [`Source`] handler, which can be used to unsubscribe the handler from source.
 pub trait HandlerId: Debug {
     fn unhandle(&self, state: &mut dyn State, dropping_binding: AnyBindingBase);
+
+    /// Returns a source description, used for introspection (see [`Bindings::nodes`]).
+    ///
+    /// Sources and targets referring to the same object member should have the same description.
+    fn describe(&self) -> SourceDescriptor { SourceDescriptor::Other(format!("{:?}", self)) }
+}
+
+/// A dependency object member kind, see [`SourceDescriptor::Member`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum MemberKind {
+    Prop,
+    Vec,
+    Event,
+}
+
+/// A binding source or target description, see [`HandlerId::describe`] and [`Target::describe`].
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SourceDescriptor {
+    /// A dependency object property, vector, or event.
+    Member {
+        kind: MemberKind,
+        /// The dependency type name.
+        owner: &'static str,
+        /// The member offset in the dependency type, e.g. [`DepProp::offset`](crate::DepProp::offset).
+        offset: usize,
+        /// The raw dependency object id.
+        id: RawId,
+    },
+    /// A binding or subject value, see [`Binding::source`].
+    Binding(AnyBindingBase),
+    /// Several sources combined into one, see [`Source::merge`].
+    Merge(Vec<SourceDescriptor>),
+    /// A custom source or target.
+    Other(String),
+}
+
+impl SourceDescriptor {
+    fn flatten_into(self, descriptors: &mut Vec<SourceDescriptor>) {
+        if let SourceDescriptor::Merge(parts) = self {
+            for part in parts {
+                part.flatten_into(descriptors);
+            }
+        } else {
+            descriptors.push(self);
+        }
+    }
+}
+
+impl Display for SourceDescriptor {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SourceDescriptor::Member { kind, owner, offset, id } => write!(f, "{:?}: {}+{} ({:?})", id, owner, offset, kind),
+            SourceDescriptor::Binding(binding) => write!(f, "{:?}", binding),
+            SourceDescriptor::Merge(parts) => {
+                write!(f, "merge(")?;
+                for (i, part) in parts.iter().enumerate() {
+                    if i != 0 { write!(f, ", ")?; }
+                    write!(f, "{}", part)?;
+                }
+                write!(f, ")")
+            },
+            SourceDescriptor::Other(s) => write!(f, "{}", s),
+        }
+    }
 }
 
 /// The [`Source::handle`] method result.
@@ -247,6 +606,9 @@ pub struct HandledSource {
 struct AnyBindingNodeVtable {
     ty: TypeId,
     drop: unsafe fn(buf: &mut BindingNodeBuf),
+    evaluate: fn(state: &mut dyn State, binding: Id<AnyBindingNode>),
+    describe: unsafe fn(buf: &BindingNodeBuf, binding: Id<AnyBindingNode>) -> BindingNodeInfo,
+    links: unsafe fn(buf: &BindingNodeBuf, sources: &mut Vec<SourceDescriptor>, targets: &mut Vec<SourceDescriptor>),
     unhandle_sources_and_release_holder: unsafe fn(
         buf: &mut BindingNodeBuf,
         state: &mut dyn State,
@@ -259,6 +621,9 @@ macro_attr! {
     struct AnyBindingNode {
         buf: BindingNodeBuf,
         vtable: &'static AnyBindingNodeVtable,
+        rank: u32,
+        location: Option<&'static Location<'static>>,
+        enabled: bool,
     }
 }
 
@@ -273,7 +638,10 @@ impl<T: Convenient> From<BindingNode<T>> for AnyBindingNode {
         );
         AnyBindingNode {
             buf: BindingNodeBuf::new(node),
-            vtable: &BindingNode::<T>::VTABLE
+            vtable: &BindingNode::<T>::VTABLE,
+            rank: 0,
+            location: None,
+            enabled: true,
         }
     }
 }
@@ -294,199 +662,2362 @@ impl AnyBindingNode {
         assert_eq!(self.vtable.ty, TypeId::of::<T>());
         unsafe { &mut *self.buf.as_mut_ptr() }
     }
+
+    fn evaluate(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+        let bindings: &Bindings = state.get();
+        let node = &bindings.nodes[binding];
+        if !node.enabled { return; }
+        let evaluate = node.vtable.evaluate;
+        evaluate(state, binding);
+    }
 }
 
 /// An arena holding all bindings data.
 /// There almost always will be only one object of that type in application.
-pub struct Bindings(Arena<AnyBindingNode>);
+pub struct Bindings {
+    nodes: Arena<AnyBindingNode>,
+    propagation: Propagation,
+    scopes: Scopes,
+    operators: Arena<OperatorNode>,
+}
 
 impl SelfState for Bindings { }
 
 const GLOBAL: Global = Global;
 
 impl Bindings {
-    pub const fn new_in(alloc: &'static dyn Allocator) -> Self { Bindings(Arena::new_in(alloc)) }
+    pub const fn new_in(alloc: &'static dyn Allocator) -> Self {
+        Bindings {
+            nodes: Arena::new_in(alloc),
+            propagation: Propagation::new(),
+            scopes: Scopes::new_in(alloc),
+            operators: Arena::new_in(alloc),
+        }
+    }
 
     pub const fn new() -> Self { Self::new_in(&GLOBAL) }
-}
 
-impl Drop for Bindings {
-    fn drop(&mut self) {
-        if !panicking() {
-            debug_assert!(self.0.items().is_empty(), "there are non-dropped bindings (count: {})", self.0.items().len());
+    fn contains(&self, node: Id<AnyBindingNode>) -> bool {
+        arena_contains(&self.nodes, node)
+    }
+
+    fn register_in_scope(&mut self, binding: Id<AnyBindingNode>) {
+        if let Some(scope) = self.scopes.current {
+            self.scopes.register(scope, ScopeMember::Binding(binding));
         }
     }
-}
 
-struct AnyBindingNodeSourcesVtable<Value: Convenient> {
-    ty: TypeId,
-    drop: unsafe fn(&mut BindingNodeSourcesBuf),
-    unhandle: unsafe fn(&mut BindingNodeSourcesBuf, state: &mut dyn State, dropping_binding: AnyBindingBase),
-    get_value: unsafe fn(&BindingNodeSourcesBuf) -> Option<Value>,
-    is_empty: unsafe fn(&BindingNodeSourcesBuf) -> bool,
-}
+    /// The maximum number of queued reentrant changes a property change can process
+    /// before it is treated as a runaway feedback loop and panics.
+    pub fn iteration_limit(&self) -> usize { self.propagation.iteration_limit }
 
-struct AnyBindingNodeSources<Value: Convenient> {
-    vtable: &'static AnyBindingNodeSourcesVtable<Value>,
-    buf: BindingNodeSourcesBuf,
-}
+    pub fn set_iteration_limit(&mut self, limit: usize) {
+        self.propagation.iteration_limit = limit;
+    }
 
-impl<Value: Convenient> Drop for AnyBindingNodeSources<Value> {
-    fn drop(&mut self) {
-        unsafe { (self.vtable.drop)(&mut self.buf); }
+    /// Whether attaching a source, which makes a binding (indirectly) depend on itself, panics.
+    ///
+    /// The check is performed in debug builds only. It is disabled by default,
+    /// because converging cycles (e.g. a trigger made of two gates) are legal.
+    pub fn deny_cycles(&self) -> bool { self.propagation.deny_cycles }
+
+    pub fn set_deny_cycles(&mut self, deny_cycles: bool) {
+        self.propagation.deny_cycles = deny_cycles;
     }
-}
 
-impl<Value: Convenient> AnyBindingNodeSources<Value> {
-    fn downcast_ref<T: 'static>(&self) -> &T {
-        assert_eq!(self.vtable.ty, TypeId::of::<T>());
-        unsafe { &*self.buf.as_ptr() }
+    /// Returns descriptions of all live binding nodes.
+    ///
+    /// Besides bindings, the list contains subjects.
+    pub fn nodes(&self) -> Vec<BindingNodeInfo> {
+        self.nodes.items().iter().map(|(id, node)| {
+            let mut info = unsafe { (node.vtable.describe)(&node.buf, id) };
+            info.location = node.location;
+            info
+        }).collect()
     }
 
-    fn downcast_mut<T: 'static>(&mut self) -> &mut T {
-        assert_eq!(self.vtable.ty, TypeId::of::<T>());
-        unsafe { &mut *self.buf.as_mut_ptr() }
+    /// Returns a report listing all live bindings with their creation locations
+    /// (captured in debug builds only), sources, and targets, a binding per line.
+    ///
+    /// It is supposed to be called when all bindings are expected to be dropped.
+    /// Live binding scopes are listed as well.
+    pub fn report_leaks(&self) -> String {
+        let mut report = String::new();
+        for node in self.nodes() {
+            write!(report, "{}<{}>", node.kind, node.value_type).unwrap();
+            if let Some(location) = node.location {
+                write!(report, " created at {}", location).unwrap();
+            }
+            writeln!(report, ", sources: [{}], targets: [{}]", join(&node.sources, ", "), join(&node.targets, ", ")).unwrap();
+        }
+        for (id, scope) in self.scopes.arena.items().iter() {
+            write!(report, "{:?}", BindingScope(id)).unwrap();
+            if let Some(location) = scope.location {
+                write!(report, " created at {}", location).unwrap();
+            }
+            writeln!(report, ", members: {}", scope.members.len()).unwrap();
+        }
+        report
     }
-}
 
-struct BindingNode<T: Convenient> {
-    sources: AnyBindingNodeSources<T>,
-    target: Option<Box<dyn Target<T>>>,
-    holder: Option<Box<dyn Holder>>,
+    /// Exports the binding graph in the Graphviz DOT format.
+    ///
+    /// Bindings are drawn as boxes, and sources and targets as ellipses.
+    /// Sources and targets with the same description (see [`HandlerId::describe`], [`Target::describe`])
+    /// are drawn as one graph node, so a property set by one binding and observed
+    /// by another one connects them.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph bindings {{").unwrap();
+        for node in self.nodes() {
+            let label = binding_label(node.binding.0);
+            write!(
+                dot, "    \"{}\" [shape=box, label=\"{}<{}>\\n{}",
+                dot_escape(&label), node.kind, dot_escape(node.value_type), dot_escape(&label)
+            ).unwrap();
+            if let Some(holder) = node.holder.as_ref() {
+                write!(dot, "\\nheld by {}", dot_escape(holder)).unwrap();
+            }
+            writeln!(dot, "\"];").unwrap();
+            let mut sources = Vec::new();
+            for source in node.sources {
+                source.flatten_into(&mut sources);
+            }
+            for source in sources {
+                writeln!(dot, "    \"{}\" -> \"{}\";", dot_escape(&source.to_string()), dot_escape(&label)).unwrap();
+            }
+            for target in &node.targets {
+                writeln!(dot, "    \"{}\" -> \"{}\";", dot_escape(&label), dot_escape(&target.to_string())).unwrap();
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
 }
 
-const BINDING_NODE_SIZE: usize = size_of::<BindingNode<!>>();
+impl Bindings {
+    /// Returns the binding sources and targets.
+    fn node_links(&self, binding: Id<AnyBindingNode>) -> (Vec<SourceDescriptor>, Vec<SourceDescriptor>) {
+        let node = &self.nodes[binding];
+        let mut sources = Vec::new();
+        let mut targets = Vec::new();
+        unsafe { (node.vtable.links)(&node.buf, &mut sources, &mut targets); }
+        (sources, targets)
+    }
 
-#[cfg_attr(target_pointer_width="64", repr(C, align(8)))]
-#[cfg_attr(target_pointer_width="32", repr(C, align(4)))]
-#[cfg_attr(not(any(target_pointer_width="32", target_pointer_width="64")), repr(C, align(1)))]
-struct BindingNodeBuf([MaybeUninit<u8>; BINDING_NODE_SIZE]);
+    /// Adds the binding links to the links index, and raises ranks (the longest path lengths
+    /// from the graph roots) of the binding and bindings depending on it.
+    ///
+    /// Removed links stay in the index until the binding is dropped, because they
+    /// cannot break the existing order. Bindings in cycles are ranked after their acyclic predecessors.
+    fn link(&mut self, binding: Id<AnyBindingNode>) {
+        let (sources, targets) = self.node_links(binding);
+        let mut rank = self.nodes[binding].rank;
+        for source in sources {
+            let writers = self.propagation.writers.get(&source).into_iter().flatten().copied();
+            let predecessor = if let SourceDescriptor::Binding(AnyBindingBase(x)) = source { Some(x) } else { None };
+            for predecessor in writers.chain(predecessor) {
+                if self.contains(predecessor) {
+                    rank = rank.max(self.nodes[predecessor].rank + 1);
+                }
+            }
+            self.propagation.observers.entry(source).or_default().insert(binding);
+        }
+        for target in targets {
+            self.propagation.writers.entry(target).or_default().insert(binding);
+        }
+        self.nodes[binding].rank = rank.min(self.rank_limit());
+        self.raise_successors(binding);
+    }
 
-impl BindingNodeBuf {
-    fn new<T: Convenient>(node: BindingNode<T>) -> BindingNodeBuf {
-        assert!(size_of::<BindingNode<T>>() == BINDING_NODE_SIZE);
-        assert!(align_of::<BindingNode<T>>() == align_of::<BindingNodeBuf>());
-        assert!(align_of::<BindingNodeBuf>() == align_of::<BindingNode<!>>());
-        let mut buf = BindingNodeBuf(unsafe { MaybeUninit::uninit().assume_init() });
-        unsafe { ptr::write(buf.as_mut_ptr(), node); }
-        buf
+    /// Removes the binding links from the links index.
+    fn unlink(&mut self, binding: Id<AnyBindingNode>) {
+        let (sources, targets) = self.node_links(binding);
+        for (links, index) in [(sources, &mut self.propagation.observers), (targets, &mut self.propagation.writers)] {
+            for link in links {
+                if let Some(bindings) = index.get_mut(&link) {
+                    bindings.remove(&binding);
+                    if bindings.is_empty() {
+                        index.remove(&link);
+                    }
+                }
+            }
+        }
     }
 
-    fn as_ptr<T: Convenient>(&self) -> *const BindingNode<T> {
-        self.0.as_ptr() as _
+    /// A rank, exceeding the longest path length in an acyclic graph.
+    fn rank_limit(&self) -> u32 {
+        u32::try_from(self.nodes.items().len()).unwrap_or(u32::MAX)
     }
 
-    fn as_mut_ptr<T: Convenient>(&mut self) -> *mut BindingNode<T> {
-        self.0.as_mut_ptr() as _
+    fn raise_successors(&mut self, binding: Id<AnyBindingNode>) {
+        let limit = self.rank_limit();
+        let mut raised = Vec::new();
+        raised.push(binding);
+        while let Some(binding) = raised.pop() {
+            let rank = self.nodes[binding].rank + 1;
+            if rank >= limit { continue; }
+            let (_, mut links) = self.node_links(binding);
+            links.push(SourceDescriptor::Binding(AnyBindingBase(binding)));
+            for link in links {
+                for &successor in self.propagation.observers.get(&link).into_iter().flatten() {
+                    if !arena_contains(&self.nodes, successor) { continue; }
+                    let successor_node = &mut self.nodes[successor];
+                    if successor_node.rank < rank {
+                        successor_node.rank = rank;
+                        raised.push(successor);
+                    }
+                }
+            }
+        }
     }
 }
 
-impl<T: Convenient> BindingNode<T> {
-    const VTABLE: AnyBindingNodeVtable = AnyBindingNodeVtable {
-        ty: TypeId::of::<T>(),
-        drop: Self::drop,
-        unhandle_sources_and_release_holder: Self::unhandle_sources_and_release_holder,
-    };
+fn arena_contains<C: Component>(arena: &Arena<C>, id: Id<C>) -> bool {
+    let index = id.into_raw().0;
+    index < arena.items().min_capacity() && arena.items().get_id(index) == Some(id)
+}
 
-    unsafe fn drop(buf: &mut BindingNodeBuf) {
-        let this: *mut BindingNode<T> = buf.as_mut_ptr();
-        ptr::drop_in_place(this);
-    }
+fn join(descriptors: &[SourceDescriptor], separator: &str) -> String {
+    descriptors.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(separator)
+}
 
-    unsafe fn unhandle_sources_and_release_holder(
-        buf: &mut BindingNodeBuf,
-        state: &mut dyn State,
-        dropping_binding: AnyBindingBase
-    ) {
-        let this: &mut BindingNode<T> = &mut *buf.as_mut_ptr();
-        this.holder.as_ref().map(|x| x.release(state));
-        (this.sources.vtable.unhandle)(&mut this.sources.buf, state, dropping_binding);
+fn binding_label(binding: Id<AnyBindingNode>) -> String {
+    format!("{:?}", AnyBindingBase(binding))
+}
+
+/// Panics if the just handled `source` makes the `binding` depend on itself.
+///
+/// The source is unhandled before panicking, so the binding stays usable.
+#[cfg(debug_assertions)]
+fn check_cycle(state: &mut dyn State, binding: Id<AnyBindingNode>, source: &dyn HandlerId) {
+    if let Some(cycle) = find_cycle(state, binding, source) {
+        reject_cycle(state, binding, source, cycle);
     }
 }
 
-macro_attr! {
-    #[derive(Educe, NewtypeComponentId!, Component!)]
-    #[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-    pub struct AnyBindingBase(Id<AnyBindingNode>);
+#[cfg(debug_assertions)]
+fn reject_cycle(state: &mut dyn State, binding: Id<AnyBindingNode>, source: &dyn HandlerId, cycle: String) -> ! {
+    source.unhandle(state, AnyBindingBase(binding));
+    panic!("binding cycle: {}", cycle);
 }
 
-impl AnyBindingBase {
-    pub fn drop_self(self, state: &mut dyn State) {
-        let bindings: &mut Bindings = state.get_mut();
-        let mut node = bindings.0.remove(self.0);
-        unsafe { (node.vtable.unhandle_sources_and_release_holder)(&mut node.buf, state, self); }
+#[cfg(debug_assertions)]
+fn find_cycle(state: &dyn State, binding: Id<AnyBindingNode>, source: &dyn HandlerId) -> Option<String> {
+    let bindings: &Bindings = state.get();
+    if !bindings.propagation.deny_cycles { return None; }
+    let mut sources = Vec::new();
+    source.describe().flatten_into(&mut sources);
+    let mut visited: BTreeMap<Id<AnyBindingNode>, Option<(Id<AnyBindingNode>, SourceDescriptor)>> = BTreeMap::new();
+    visited.insert(binding, None);
+    let mut queue = alloc::collections::VecDeque::new();
+    queue.push_back(binding);
+    while let Some(vertex) = queue.pop_front() {
+        let links = bindings.node_links(vertex).1.into_iter().chain(once(SourceDescriptor::Binding(AnyBindingBase(vertex))));
+        for link in links {
+            if sources.contains(&link) {
+                let mut path = Vec::new();
+                if link != SourceDescriptor::Binding(AnyBindingBase(vertex)) {
+                    path.push(link);
+                }
+                let mut current = vertex;
+                loop {
+                    path.push(SourceDescriptor::Binding(AnyBindingBase(current)));
+                    let (parent, link) = if let Some(parent) = visited[&current].clone() { parent } else { break; };
+                    if link != SourceDescriptor::Binding(AnyBindingBase(parent)) {
+                        path.push(link);
+                    }
+                    current = parent;
+                }
+                path.reverse();
+                return Some(format!("{} -> {}", join(&path, " -> "), binding_label(binding)));
+            }
+            for &next in bindings.propagation.observers.get(&link).into_iter().flatten() {
+                if !bindings.contains(next) { continue; }
+                if let alloc::collections::btree_map::Entry::Vacant(entry) = visited.entry(next) {
+                    entry.insert(Some((vertex, link.clone())));
+                    queue.push_back(next);
+                }
+            }
+        }
     }
+    None
 }
 
-macro_attr! {
-    #[derive(Educe, NewtypeComponentId!)]
-    #[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-    pub struct BindingBase<T: Convenient>(Id<AnyBindingNode>, PhantomType<T>);
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
-impl<T: Convenient> BindingBase<T> {
-    pub fn set_target(self, state: &mut dyn State, target: Box<dyn Target<T>>) {
-        let bindings: &mut Bindings = state.get_mut();
-        let node = bindings.0[self.0].downcast_mut::<T>();
-        node.target = Some(target);
-        assert!(
-            unsafe { (node.sources.vtable.is_empty)(&node.sources.buf) },
-            "set_target should be called before any set_source_*"
-        );
-    }
+/// A binding node description, returned by the [`Bindings::nodes`] method.
+#[derive(Debug, Clone)]
+pub struct BindingNodeInfo {
+    pub binding: AnyBindingBase,
+    /// The node kind, e.g. `"Binding2"` or `"Subject"`.
+    pub kind: &'static str,
+    pub value_type: &'static str,
+    /// The node creation location, captured in debug builds only.
+    pub location: Option<&'static Location<'static>>,
+    /// Attached sources descriptions, see [`HandlerId::describe`].
+    pub sources: Vec<SourceDescriptor>,
+    /// The main and additional targets descriptions, see [`Target::describe`].
+    pub targets: Vec<SourceDescriptor>,
+    pub holder: Option<String>,
+    /// Attached downstream handlers count.
+    pub subscribers: usize,
+}
 
-    pub fn set_holder(self, state: &mut dyn State, holder: Box<dyn Holder>) {
+impl Drop for Bindings {
+    fn drop(&mut self) {
+        if !panicking() {
+            debug_assert!(
+                self.nodes.items().is_empty() && self.scopes.arena.items().is_empty() && self.operators.items().is_empty(),
+                "there are non-dropped bindings (count: {}, scopes: {}, operators: {}):\n{}",
+                self.nodes.items().len(), self.scopes.arena.items().len(), self.operators.items().len(), self.report_leaks()
+            );
+        }
+    }
+}
+
+/// A binding evaluation mode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BindingSchedule {
+    /// The binding is evaluated immediately on every source change.
+    Immediate,
+    /// The binding is queued in the [`Dispatcher`] and evaluated on the [`Dispatcher::drain`] call.
+    /// Bindings with greater priority are evaluated first.
+    ///
+    /// Requires the [`Dispatcher`] state part, queuing a binding panics without it.
+    Deferred(i32),
+    /// The binding is queued in the [`Dispatcher`] and evaluated on the [`Dispatcher::drain_idle`] call.
+    ///
+    /// Requires the [`Dispatcher`] state part, queuing a binding panics without it.
+    OnIdle,
+    /// The binding is evaluated once after the outermost mutation (a property change, event raising,
+    /// [`Subject::push`] call, style application, [`Clock::tick`] or [`Dispatcher::drain`] call, etc.)
+    /// has finished, so it sees a consistent snapshot of its sources.
+    ///
+    /// Queued consistent bindings are evaluated in topological order of the binding graph
+    /// (bindings are linked through binding sources and through targets observed by other bindings,
+    /// see [`Bindings::nodes`]), so a binding depending on a changed value through
+    /// paths of different lengths is evaluated once.
+    Consistent,
+}
+
+fn enqueue(state: &mut dyn State, binding: Id<AnyBindingNode>, schedule: BindingSchedule) {
+    if schedule == BindingSchedule::Consistent {
+        Bindings::enqueue_consistent(state, binding);
+    } else {
+        Dispatcher::enqueue(state, binding, schedule);
+    }
+}
+
+#[cfg(debug_assertions)]
+#[derive(Debug, Copy, Clone)]
+enum ChainLink {
+    Prop { owner: &'static str, offset: usize, id: RawId },
+    Binding(Id<AnyBindingNode>),
+}
+
+#[cfg(debug_assertions)]
+impl ChainLink {
+    fn describe(self) -> String {
+        match self {
+            ChainLink::Prop { owner, offset, id } => prop_label(owner, offset, id),
+            ChainLink::Binding(binding) => binding_label(binding),
+        }
+    }
+}
+
+fn prop_label(owner: &'static str, offset: usize, id: RawId) -> String {
+    format!("{:?}: {}+{}", id, owner, offset)
+}
+
+/// The default [`Bindings::iteration_limit`] value.
+pub const DEFAULT_ITERATION_LIMIT: usize = 1000;
+
+#[derive(Debug)]
+struct Propagation {
+    depth: usize,
+    evaluating: Option<u32>,
+    queue: BTreeSet<(u32, u64, Id<AnyBindingNode>)>,
+    queued: BTreeMap<Id<AnyBindingNode>, (u32, u64)>,
+    seq: u64,
+    /// Bindings observing every source.
+    observers: BTreeMap<SourceDescriptor, BTreeSet<Id<AnyBindingNode>>>,
+    /// Bindings targeting every target.
+    writers: BTreeMap<SourceDescriptor, BTreeSet<Id<AnyBindingNode>>>,
+    #[cfg(debug_assertions)]
+    chain: Vec<ChainLink>,
+    #[cfg(debug_assertions)]
+    last_cycle: Vec<ChainLink>,
+    iteration_limit: usize,
+    deny_cycles: bool,
+}
+
+impl Propagation {
+    const fn new() -> Self {
+        Propagation {
+            depth: 0,
+            evaluating: None,
+            queue: BTreeSet::new(),
+            queued: BTreeMap::new(),
+            seq: 0,
+            observers: BTreeMap::new(),
+            writers: BTreeMap::new(),
+            #[cfg(debug_assertions)]
+            chain: Vec::new(),
+            #[cfg(debug_assertions)]
+            last_cycle: Vec::new(),
+            iteration_limit: DEFAULT_ITERATION_LIMIT,
+            deny_cycles: false,
+        }
+    }
+
+    fn enqueue(&mut self, binding: Id<AnyBindingNode>, rank: u32) {
+        if let Some(&(queued_rank, seq)) = self.queued.get(&binding) {
+            if queued_rank >= rank { return; }
+            self.queue.remove(&(queued_rank, seq, binding));
+        }
+        self.queue.insert((rank, self.seq, binding));
+        self.queued.insert(binding, (rank, self.seq));
+        self.seq += 1;
+    }
+
+    fn cancel(&mut self, binding: Id<AnyBindingNode>) {
+        if let Some((rank, seq)) = self.queued.remove(&binding) {
+            self.queue.remove(&(rank, seq, binding));
+        }
+    }
+
+    fn pop(&mut self) -> Option<(u32, Id<AnyBindingNode>)> {
+        let &(rank, seq, binding) = self.queue.iter().next()?;
+        self.queue.remove(&(rank, seq, binding));
+        self.queued.remove(&binding);
+        Some((rank, binding))
+    }
+
+    fn is_empty(&self) -> bool { self.queue.is_empty() }
+}
+
+impl Bindings {
+    fn enqueue_consistent(state: &mut dyn State, binding: Id<AnyBindingNode>) {
         let bindings: &mut Bindings = state.get_mut();
-        let node = bindings.0[self.0].downcast_mut::<T>();
-        node.holder = Some(holder);
-        assert!(
-            unsafe { (node.sources.vtable.is_empty)(&node.sources.buf) },
-            "set_holder should be called before any set_source_*"
-        );
+        let node = &mut bindings.nodes[binding];
+        if let Some(rank) = bindings.propagation.evaluating {
+            node.rank = node.rank.max(rank + 1);
+        }
+        let rank = node.rank;
+        bindings.propagation.enqueue(binding, rank);
+        if bindings.propagation.depth == 0 {
+            Self::drain_consistent(state);
+        }
     }
 
-    pub fn dispatch<Context: Clone + 'static>(
-        self,
+    fn drain_consistent(state: &mut dyn State) {
+        Self::run_propagation(state, |state| {
+            loop {
+                let bindings: &mut Bindings = state.get_mut();
+                let (rank, binding) = if let Some(queued) = bindings.propagation.pop() { queued } else { break; };
+                let evaluating = replace(&mut bindings.propagation.evaluating, Some(rank));
+                AnyBindingNode::evaluate(state, binding);
+                let bindings: &mut Bindings = state.get_mut();
+                bindings.propagation.evaluating = evaluating;
+            }
+        })
+    }
+
+    #[cfg(debug_assertions)]
+    pub(crate) fn enter_prop(state: &mut dyn State, owner: &'static str, offset: usize, id: RawId) {
+        if let Some(bindings) = state.get_mut_raw(TypeId::of::<Bindings>()) {
+            let bindings = bindings.downcast_mut::<Bindings>().unwrap();
+            bindings.propagation.chain.push(ChainLink::Prop { owner, offset, id });
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    pub(crate) fn leave_prop(state: &mut dyn State) {
+        if let Some(bindings) = state.get_mut_raw(TypeId::of::<Bindings>()) {
+            let bindings = bindings.downcast_mut::<Bindings>().unwrap();
+            bindings.propagation.chain.pop();
+        }
+    }
+
+    /// Remembers the chain, which led to a reentrant property change.
+    #[cfg(debug_assertions)]
+    pub(crate) fn prop_queued(state: &mut dyn State, owner: &'static str, offset: usize, id: RawId) {
+        if let Some(bindings) = state.get_mut_raw(TypeId::of::<Bindings>()) {
+            let bindings = bindings.downcast_mut::<Bindings>().unwrap();
+            let link = ChainLink::Prop { owner, offset, id };
+            let start = bindings.propagation.chain.iter().rposition(|x| matches!(x,
+                &ChainLink::Prop { owner: o, offset: f, id: i } if o == owner && f == offset && i == id
+            )).unwrap_or(0);
+            bindings.propagation.last_cycle.clear();
+            bindings.propagation.last_cycle.extend_from_slice(&bindings.propagation.chain[start ..]);
+            bindings.propagation.last_cycle.push(link);
+        }
+    }
+
+    /// Panics if a property change loop made too many iterations.
+    pub(crate) fn check_iterations(state: &dyn State, iterations: usize, owner: &'static str, offset: usize, id: RawId) {
+        let bindings = state.get_raw(TypeId::of::<Bindings>()).map(|x| x.downcast_ref::<Bindings>().unwrap());
+        let limit = bindings.map_or(DEFAULT_ITERATION_LIMIT, |x| x.propagation.iteration_limit);
+        if iterations <= limit { return; }
+        let prop = prop_label(owner, offset, id);
+        #[cfg(debug_assertions)]
+        let cycle = bindings.map(|x| x.propagation.last_cycle.iter().map(|x| x.describe()).collect::<Vec<_>>().join(" -> "));
+        #[cfg(not(debug_assertions))]
+        let cycle: Option<String> = None;
+        if let Some(cycle) = cycle.filter(|x| !x.is_empty()) {
+            panic!("runaway feedback loop: '{}' changed more than {} times, the last cycle: {}", prop, limit, cycle);
+        } else {
+            panic!("runaway feedback loop: '{}' changed more than {} times", prop, limit);
+        }
+    }
+
+    /// Calls `f` in a propagation scope: consistent bindings queued inside it are evaluated
+    /// after the outermost scope is left.
+    ///
+    /// The scope is left even if `f` panics, but queued bindings are not evaluated while unwinding.
+    pub(crate) fn run_propagation<R>(state: &mut dyn State, f: impl FnOnce(&mut dyn State) -> R) -> R {
+        if let Some(bindings) = state.get_mut_raw(TypeId::of::<Bindings>()) {
+            let bindings = bindings.downcast_mut::<Bindings>().unwrap();
+            bindings.propagation.depth += 1;
+        } else {
+            return f(state);
+        }
+        let mut guard = PropagationGuard { state, completed: false };
+        // SAFETY: `state` is not used after the guard creation, so the reference passed to `f`
+        // is the only live access to the state until `f` returns or unwinds.
+        // The guard dereferences the pointer only in `drop`, after the reference is gone.
+        let res = f(unsafe { &mut *guard.state });
+        guard.completed = true;
+        res
+    }
+}
+
+struct PropagationGuard {
+    state: *mut dyn State,
+    /// `false` if the guard is dropped while unwinding.
+    completed: bool,
+}
+
+impl Drop for PropagationGuard {
+    fn drop(&mut self) {
+        // SAFETY: see `Bindings::run_propagation`.
+        let state = unsafe { &mut *self.state };
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.propagation.depth -= 1;
+        if bindings.propagation.depth != 0 { return; }
+        bindings.propagation.evaluating = None;
+        if self.completed && !bindings.propagation.is_empty() {
+            Bindings::drain_consistent(state);
+        }
+    }
+}
+
+/// A queue of bindings with non-immediate [`BindingSchedule`].
+///
+/// A queued binding is evaluated once, no matter how many source changes happened
+/// since it was queued. It is evaluated from cached source values, so sources without
+/// caching (see [`NoCache`]) are seen as having no value.
+#[derive(Debug)]
+pub struct Dispatcher {
+    queued: BTreeSet<Id<AnyBindingNode>>,
+    deferred: BTreeMap<(Reverse<i32>, u64), Id<AnyBindingNode>>,
+    idle: BTreeMap<u64, Id<AnyBindingNode>>,
+    seq: u64,
+}
+
+impl SelfState for Dispatcher { }
+
+impl const Default for Dispatcher {
+    fn default() -> Self { Dispatcher::new() }
+}
+
+impl Dispatcher {
+    pub const fn new() -> Self {
+        Dispatcher { queued: BTreeSet::new(), deferred: BTreeMap::new(), idle: BTreeMap::new(), seq: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool { self.queued.is_empty() }
+
+    fn enqueue(state: &mut dyn State, binding: Id<AnyBindingNode>, schedule: BindingSchedule) {
+        let dispatcher = state.get_mut_raw(TypeId::of::<Dispatcher>())
+            .expect("deferred and on-idle bindings require the Dispatcher state part")
+            .downcast_mut::<Dispatcher>().unwrap();
+        if !dispatcher.queued.insert(binding) { return; }
+        match schedule {
+            BindingSchedule::Immediate | BindingSchedule::Consistent => unreachable!(),
+            BindingSchedule::Deferred(priority) => { dispatcher.deferred.insert((Reverse(priority), dispatcher.seq), binding); },
+            BindingSchedule::OnIdle => { dispatcher.idle.insert(dispatcher.seq, binding); },
+        }
+        dispatcher.seq += 1;
+    }
+
+    fn cancel(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+        if let Some(dispatcher) = state.get_mut_raw(TypeId::of::<Dispatcher>()) {
+            let dispatcher = dispatcher.downcast_mut::<Dispatcher>().unwrap();
+            dispatcher.queued.remove(&binding);
+        }
+    }
+
+    /// Evaluates all bindings queued with the [`BindingSchedule::Deferred`] mode,
+    /// including bindings queued while draining.
+    pub fn drain(state: &mut dyn State) {
+        Recorder::run_driven(state, |state| {
+            loop {
+                Bindings::run_propagation(state, |state| loop {
+                    let dispatcher: &mut Dispatcher = state.get_mut();
+                    let binding = if let Some(&key) = dispatcher.deferred.keys().next() {
+                        dispatcher.deferred.remove(&key).unwrap()
+                    } else {
+                        break;
+                    };
+                    if dispatcher.queued.remove(&binding) {
+                        AnyBindingNode::evaluate(state, binding);
+                    }
+                });
+                // consistent bindings evaluated on leaving can queue more bindings
+                let dispatcher: &Dispatcher = state.get();
+                if dispatcher.deferred.is_empty() { break; }
+            }
+        })
+    }
+
+    /// Evaluates all queued bindings: deferred ones first, and then ones queued
+    /// with the [`BindingSchedule::OnIdle`] mode.
+    pub fn drain_idle(state: &mut dyn State) {
+        Recorder::run_driven(state, |state| {
+            loop {
+                Bindings::run_propagation(state, |state| loop {
+                    Self::drain(state);
+                    let dispatcher: &mut Dispatcher = state.get_mut();
+                    let binding = if let Some(&key) = dispatcher.idle.keys().next() {
+                        dispatcher.idle.remove(&key).unwrap()
+                    } else {
+                        break;
+                    };
+                    if dispatcher.queued.remove(&binding) {
+                        AnyBindingNode::evaluate(state, binding);
+                    }
+                });
+                let dispatcher: &Dispatcher = state.get();
+                if dispatcher.deferred.is_empty() && dispatcher.idle.is_empty() { break; }
+            }
+        })
+    }
+}
+
+struct AnyBindingNodeSourcesVtable<Value: Convenient> {
+    ty: TypeId,
+    kind: &'static str,
+    describe: unsafe fn(&BindingNodeSourcesBuf) -> Vec<SourceDescriptor>,
+    drop: unsafe fn(&mut BindingNodeSourcesBuf),
+    unhandle: unsafe fn(&mut BindingNodeSourcesBuf, state: &mut dyn State, dropping_binding: AnyBindingBase),
+    get_value: unsafe fn(&BindingNodeSourcesBuf) -> Option<Value>,
+    is_empty: unsafe fn(&BindingNodeSourcesBuf) -> bool,
+    evaluate: fn(state: &mut dyn State, binding: Id<AnyBindingNode>),
+}
+
+#[track_caller]
+fn caller_location() -> Option<&'static Location<'static>> {
+    if cfg!(debug_assertions) { Some(Location::caller()) } else { None }
+}
+
+fn evaluate_nothing(_state: &mut dyn State, _binding: Id<AnyBindingNode>) { }
+
+unsafe fn describe_no_sources(_buf: &BindingNodeSourcesBuf) -> Vec<SourceDescriptor> { Vec::new() }
+
+struct AnyBindingNodeSources<Value: Convenient> {
+    vtable: &'static AnyBindingNodeSourcesVtable<Value>,
+    buf: BindingNodeSourcesBuf,
+}
+
+impl<Value: Convenient> Drop for AnyBindingNodeSources<Value> {
+    fn drop(&mut self) {
+        unsafe { (self.vtable.drop)(&mut self.buf); }
+    }
+}
+
+impl<Value: Convenient> AnyBindingNodeSources<Value> {
+    fn downcast_ref<T: 'static>(&self) -> &T {
+        assert_eq!(self.vtable.ty, TypeId::of::<T>());
+        unsafe { &*self.buf.as_ptr() }
+    }
+
+    fn downcast_mut<T: 'static>(&mut self) -> &mut T {
+        assert_eq!(self.vtable.ty, TypeId::of::<T>());
+        unsafe { &mut *self.buf.as_mut_ptr() }
+    }
+
+    fn place_closure<T: 'static, F: 'static>(&mut self, param: impl FnOnce(&mut T) -> &mut Closure<F>, f: F) {
+        assert_eq!(self.vtable.ty, TypeId::of::<T>());
+        unsafe { self.buf.place_closure(param, f); }
+    }
+}
+
+#[derive(Educe)]
+#[educe(Clone)]
+enum BindingOutput<T: Convenient> {
+    Handler(Rc<dyn Handler<T>>),
+    Target(Rc<dyn Target<T>>),
+}
+
+impl<T: Convenient> BindingOutput<T> {
+    fn execute(&self, state: &mut dyn State, value: T) {
+        match self {
+            BindingOutput::Handler(handler) => handler.execute(state, value),
+            BindingOutput::Target(target) => target.execute(state, value),
+        }
+    }
+}
+
+macro_attr! {
+    #[derive(Component!(class=BindingOutputNodeComponent))]
+    struct BindingOutputNode<T: Convenient> {
+        output: BindingOutput<T>,
+        holder: Option<Box<dyn Holder>>,
+    }
+}
+
+/// Binding node parts, which are not needed to compute the binding value:
+/// the binding holder and all binding outputs (the main target, additional targets,
+/// and downstream handlers, see [`BindingBase::source`]).
+///
+/// They are stored out of line to keep binding nodes small.
+struct BindingOutputs<T: Convenient> {
+    holder: Option<Box<dyn Holder>>,
+    target: Option<Id<BindingOutputNode<T>>>,
+    nodes: Arena<BindingOutputNode<T>>,
+}
+
+struct BindingNode<T: Convenient> {
+    sources: AnyBindingNodeSources<T>,
+    outputs: Option<Box<BindingOutputs<T>>>,
+    schedule: BindingSchedule,
+}
+
+const BINDING_NODE_SIZE: usize = size_of::<BindingNode<!>>();
+
+#[cfg_attr(target_pointer_width="64", repr(C, align(8)))]
+#[cfg_attr(target_pointer_width="32", repr(C, align(4)))]
+#[cfg_attr(not(any(target_pointer_width="32", target_pointer_width="64")), repr(C, align(1)))]
+struct BindingNodeBuf([MaybeUninit<u8>; BINDING_NODE_SIZE]);
+
+impl BindingNodeBuf {
+    fn new<T: Convenient>(node: BindingNode<T>) -> BindingNodeBuf {
+        assert!(size_of::<BindingNode<T>>() == BINDING_NODE_SIZE);
+        assert!(align_of::<BindingNode<T>>() == align_of::<BindingNodeBuf>());
+        assert!(align_of::<BindingNodeBuf>() == align_of::<BindingNode<!>>());
+        let mut buf = BindingNodeBuf(unsafe { MaybeUninit::uninit().assume_init() });
+        unsafe { ptr::write(buf.as_mut_ptr(), node); }
+        buf
+    }
+
+    fn as_ptr<T: Convenient>(&self) -> *const BindingNode<T> {
+        self.0.as_ptr() as _
+    }
+
+    fn as_mut_ptr<T: Convenient>(&mut self) -> *mut BindingNode<T> {
+        self.0.as_mut_ptr() as _
+    }
+}
+
+impl<T: Convenient> BindingNode<T> {
+    fn new(sources: AnyBindingNodeSources<T>) -> Self {
+        BindingNode { sources, outputs: None, schedule: BindingSchedule::Immediate }
+    }
+
+    const VTABLE: AnyBindingNodeVtable = AnyBindingNodeVtable {
+        ty: TypeId::of::<T>(),
+        drop: Self::drop,
+        unhandle_sources_and_release_holder: Self::unhandle_sources_and_release_holder,
+        evaluate: Self::evaluate,
+        describe: Self::describe,
+        links: Self::links,
+    };
+
+    fn evaluate(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+        let bindings: &Bindings = state.get();
+        let evaluate = bindings.nodes[binding].downcast_ref::<T>().sources.vtable.evaluate;
+        evaluate(state, binding);
+    }
+
+    unsafe fn describe(buf: &BindingNodeBuf, binding: Id<AnyBindingNode>) -> BindingNodeInfo {
+        let this: &BindingNode<T> = &*buf.as_ptr();
+        BindingNodeInfo {
+            binding: AnyBindingBase(binding),
+            kind: this.sources.vtable.kind,
+            value_type: type_name::<T>(),
+            location: None,
+            sources: (this.sources.vtable.describe)(&this.sources.buf),
+            targets: this.output_nodes().filter_map(|x| match &x.output {
+                BindingOutput::Target(target) => Some(target.describe()),
+                BindingOutput::Handler(_) => None,
+            }).collect(),
+            holder: this.outputs.as_ref().and_then(|x| x.holder.as_ref()).map(|x| x.describe()),
+            subscribers: this.output_nodes().filter(|x| matches!(x.output, BindingOutput::Handler(_))).count(),
+        }
+    }
+
+    unsafe fn links(buf: &BindingNodeBuf, sources: &mut Vec<SourceDescriptor>, targets: &mut Vec<SourceDescriptor>) {
+        let this: &BindingNode<T> = &*buf.as_ptr();
+        for source in (this.sources.vtable.describe)(&this.sources.buf) {
+            source.flatten_into(sources);
+        }
+        targets.extend(this.output_nodes().filter_map(|x| match &x.output {
+            BindingOutput::Target(target) => Some(target.describe()),
+            BindingOutput::Handler(_) => None,
+        }));
+    }
+
+    unsafe fn drop(buf: &mut BindingNodeBuf) {
+        let this: *mut BindingNode<T> = buf.as_mut_ptr();
+        ptr::drop_in_place(this);
+    }
+
+    unsafe fn unhandle_sources_and_release_holder(
+        buf: &mut BindingNodeBuf,
         state: &mut dyn State,
-        context: Context,
-        execute: fn(state: &mut dyn State, context: Context, value: T) -> Re<!>
+        dropping_binding: AnyBindingBase
     ) {
-        self.set_target(state, Box::new(DispatchTarget { context, execute }));
+        let this: &mut BindingNode<T> = &mut *buf.as_mut_ptr();
+        let outputs = this.outputs.take();
+        if let Some(outputs) = outputs.as_ref() {
+            outputs.holder.as_ref().map(|x| x.release(state));
+            for node in outputs.nodes.items().values() {
+                node.holder.as_ref().map(|x| x.release(state));
+            }
+        }
+        (this.sources.vtable.unhandle)(&mut this.sources.buf, state, dropping_binding);
+        for node in outputs.into_iter().flat_map(|x| x.nodes.into_items().into_values()) {
+            if let BindingOutput::Handler(handler) = node.output {
+                clone_box(&*handler).into_any().clear(state);
+            }
+        }
+    }
+
+    fn output_nodes(&self) -> impl Iterator<Item=&BindingOutputNode<T>> {
+        self.outputs.iter().flat_map(|x| x.nodes.items().values())
+    }
+
+    fn outputs_mut(&mut self) -> &mut BindingOutputs<T> {
+        self.outputs.get_or_insert_with(|| Box::new(BindingOutputs { holder: None, target: None, nodes: Arena::new() }))
+    }
+
+    fn add_output(&mut self, output: BindingOutput<T>, holder: Option<Box<dyn Holder>>) -> Id<BindingOutputNode<T>> {
+        self.outputs_mut().nodes.insert(|id| (BindingOutputNode { output, holder }, id))
+    }
+
+    fn remove_output(&mut self, output: Id<BindingOutputNode<T>>) -> Option<BindingOutputNode<T>> {
+        let outputs = self.outputs.as_mut()?;
+        if !arena_contains(&outputs.nodes, output) { return None; }
+        Some(outputs.nodes.remove(output))
+    }
+
+    fn outputs(&self, binding: Id<AnyBindingNode>) -> BindingNodeOutputs<T> {
+        #[cfg(not(debug_assertions))]
+        let _ = binding;
+        BindingNodeOutputs {
+            #[cfg(debug_assertions)]
+            binding,
+            outputs: self.output_nodes().map(|x| x.output.clone()).collect(),
+        }
+    }
+}
+
+struct BindingNodeOutputs<T: Convenient> {
+    #[cfg(debug_assertions)]
+    binding: Id<AnyBindingNode>,
+    outputs: Vec<BindingOutput<T>>,
+}
+
+impl<T: Convenient> BindingNodeOutputs<T> {
+    fn execute(self, state: &mut dyn State, value: T) {
+        let (last, outputs) = if let Some(outputs) = self.outputs.split_last() { outputs } else { return; };
+        #[cfg(debug_assertions)]
+        state.get_mut::<Bindings>().propagation.chain.push(ChainLink::Binding(self.binding));
+        Recorder::run_driven(state, |state| {
+            for output in outputs {
+                output.execute(state, value.clone());
+            }
+            last.execute(state, value);
+        });
+        #[cfg(debug_assertions)]
+        state.get_mut::<Bindings>().propagation.chain.pop();
+    }
+}
+
+macro_attr! {
+    #[derive(Educe, NewtypeComponentId!, Component!)]
+    #[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+    pub struct AnyBindingBase(Id<AnyBindingNode>);
+}
+
+impl AnyBindingBase {
+    /// An id matching no binding, passed to [`HandlerId::unhandle`]
+    /// when a source is detached without dropping any binding.
+    fn none() -> Self {
+        AnyBindingBase(Id::from_raw((usize::MAX, NonZeroUsize::new(1).unwrap())))
+    }
+
+    pub fn drop_self(self, state: &mut dyn State) {
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.propagation.cancel(self.0);
+        bindings.unlink(self.0);
+        bindings.scopes.unregister(ScopeMember::Binding(self.0));
+        let mut node = bindings.nodes.remove(self.0);
+        unsafe { (node.vtable.unhandle_sources_and_release_holder)(&mut node.buf, state, self); }
+        Dispatcher::cancel(state, self.0);
+    }
+}
+
+macro_attr! {
+    #[derive(Educe, NewtypeComponentId!)]
+    #[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+    pub struct BindingBase<T: Convenient>(Id<AnyBindingNode>, PhantomType<T>);
+}
+
+impl<T: Convenient> BindingBase<T> {
+    pub fn set_target(self, state: &mut dyn State, target: Box<dyn Target<T>>) {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        let target = node.add_output(BindingOutput::Target(target.into()), None);
+        let outputs = node.outputs_mut();
+        if let Some(old) = outputs.target.replace(target) {
+            outputs.nodes.remove(old);
+        }
+        assert!(
+            unsafe { (node.sources.vtable.is_empty)(&node.sources.buf) },
+            "set_target should be called before any set_source_*"
+        );
+        bindings.link(self.0);
+    }
+
+    pub fn set_holder(self, state: &mut dyn State, holder: Box<dyn Holder>) {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        node.outputs_mut().holder = Some(holder);
+        assert!(
+            unsafe { (node.sources.vtable.is_empty)(&node.sources.buf) },
+            "set_holder should be called before any set_source_*"
+        );
+    }
+
+    /// Sets the binding evaluation mode. The default mode is [`BindingSchedule::Immediate`].
+    pub fn set_schedule(self, state: &mut dyn State, schedule: BindingSchedule) {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        node.schedule = schedule;
+    }
+
+    pub fn is_enabled(self, state: &dyn State) -> bool {
+        let bindings: &Bindings = state.get();
+        bindings.nodes[self.0].enabled
+    }
+
+    /// Pauses or resumes the binding. Bindings are enabled by default.
+    ///
+    /// A disabled binding stays subscribed to its sources and keeps caching their values,
+    /// but is not evaluated, so neither its dispatch function nor its targets are called.
+    /// Re-enabling a binding with this method does not evaluate it until the next source change,
+    /// use [`resume`](BindingBase::resume) to push the current value immediately.
+    pub fn set_enabled(self, state: &mut dyn State, enabled: bool) {
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.nodes[self.0].enabled = enabled;
+    }
+
+    /// Enables the binding and evaluates it from the cached source values
+    /// (sources without caching, see [`NoCache`], are seen as having no value).
+    pub fn resume(self, state: &mut dyn State) {
+        self.set_enabled(state, true);
+        AnyBindingNode::evaluate(state, self.0);
+    }
+
+    pub fn dispatch<Context: Clone + 'static>(
+        self,
+        state: &mut dyn State,
+        context: Context,
+        execute: fn(state: &mut dyn State, context: Context, value: T) -> Re<!>
+    ) {
+        self.set_target(state, Box::new(DispatchTarget { context, execute }));
+    }
+
+    pub fn set_target_fn<Context: Clone + 'static>(
+        self,
+        state: &mut dyn State,
+        context: Context,
+        execute: fn(state: &mut dyn State, context: Context, value: T)
+    ) {
+        self.set_target(state, Box::new(FnTarget { context, execute }));
+    }
+
+    pub fn dispatch_closure(
+        self,
+        state: &mut dyn State,
+        execute: impl Fn(&mut dyn State, T) -> Re<!> + Clone + 'static
+    ) {
+        self.set_target(state, Box::new(ClosureDispatchTarget(execute)));
+    }
+
+    pub fn set_target_closure(
+        self,
+        state: &mut dyn State,
+        execute: impl Fn(&mut dyn State, T) + Clone + 'static
+    ) {
+        self.set_target(state, Box::new(ClosureTarget(execute)));
+    }
+
+    pub fn drop_self(self, state: &mut dyn State) {
+        AnyBindingBase::from(self).drop_self(state);
+    }
+
+    /// Adds an additional binding target, and sends the current binding value (if any) to it.
+    ///
+    /// Unlike the main target (see [`set_target`](BindingBase::set_target)),
+    /// additional targets can be added and removed at any moment.
+    /// The current value is known for bindings computing it from cached source values
+    /// (see [`Binding::get_value`]), but not for [`BindingExt1`], [`BindingExt2`], ... bindings.
+    ///
+    /// The `holder` (if any) is released when the binding is dropped.
+    pub fn add_target(
+        self,
+        state: &mut dyn State,
+        target: Box<dyn Target<T>>,
+        holder: Option<Box<dyn Holder>>
+    ) -> BindingTargetId<T> {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        let value = unsafe { (node.sources.vtable.get_value)(&node.sources.buf) };
+        let target: Rc<dyn Target<T>> = target.into();
+        let id = node.add_output(BindingOutput::Target(target.clone()), holder);
+        bindings.link(self.0);
+        if let Some(value) = value {
+            target.execute(state, value);
+        }
+        BindingTargetId { binding: self.0, target: id }
+    }
+
+    /// Returns a source sending the binding values to other bindings.
+    ///
+    /// The binding value is not cached, so a newly attached binding does not receive
+    /// the current value. Use [`Binding::source`] if it is required.
+    pub fn source(self) -> BindingSource<T, NoCache> {
+        BindingSource(self.0, PhantomType::new())
+    }
+}
+
+macro_attr! {
+    #[derive(Educe, NewtypeComponentId!)]
+    #[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+    pub struct Binding<T: Convenient>(Id<AnyBindingNode>, PhantomType<T>);
+}
+
+impl<T: Convenient> From<Binding<T>> for BindingBase<T> {
+    fn from(binding: Binding<T>) -> Self {
+        BindingBase(binding.0, PhantomType::new())
+    }
+}
+
+impl<T: Convenient> From<Binding<T>> for AnyBindingBase {
+    fn from(v: Binding<T>) -> AnyBindingBase {
+        AnyBindingBase(v.0)
+    }
+}
+
+impl<T: Convenient> Binding<T> {
+    pub fn set_target(self, state: &mut dyn State, target: Box<dyn Target<T>>) {
+        BindingBase::from(self).set_target(state, target);
+    }
+
+    pub fn set_holder(self, state: &mut dyn State, holder: Box<dyn Holder>) {
+        BindingBase::from(self).set_holder(state, holder);
+    }
+
+    pub fn set_schedule(self, state: &mut dyn State, schedule: BindingSchedule) {
+        BindingBase::from(self).set_schedule(state, schedule);
+    }
+
+    pub fn is_enabled(self, state: &dyn State) -> bool {
+        BindingBase::from(self).is_enabled(state)
+    }
+
+    pub fn set_enabled(self, state: &mut dyn State, enabled: bool) {
+        BindingBase::from(self).set_enabled(state, enabled);
+    }
+
+    pub fn resume(self, state: &mut dyn State) {
+        BindingBase::from(self).resume(state);
+    }
+
+    pub fn dispatch<Context: Clone + 'static>(
+        self,
+        state: &mut dyn State,
+        context: Context,
+        execute: fn(state: &mut dyn State, context: Context, value: T) -> Re<!>
+    ) {
+        BindingBase::from(self).dispatch(state, context, execute);
+    }
+
+    pub fn set_target_fn<Context: Clone + 'static>(
+        self,
+        state: &mut dyn State,
+        context: Context,
+        execute: fn(state: &mut dyn State, context: Context, value: T)
+    ) {
+        BindingBase::from(self).set_target_fn(state, context, execute);
+    }
+
+    pub fn dispatch_closure(
+        self,
+        state: &mut dyn State,
+        execute: impl Fn(&mut dyn State, T) -> Re<!> + Clone + 'static
+    ) {
+        BindingBase::from(self).dispatch_closure(state, execute);
+    }
+
+    pub fn set_target_closure(
+        self,
+        state: &mut dyn State,
+        execute: impl Fn(&mut dyn State, T) + Clone + 'static
+    ) {
+        BindingBase::from(self).set_target_closure(state, execute);
+    }
+
+    pub fn drop_self(self, state: &mut dyn State) {
+        AnyBindingBase::from(self).drop_self(state);
+    }
+
+    pub fn get_value(self, state: &dyn State) -> Option<T> {
+        let bindings: &Bindings = state.get();
+        let node = bindings.nodes[self.0].downcast_ref::<T>();
+        unsafe { (node.sources.vtable.get_value)(&node.sources.buf) }
+    }
+
+    /// Returns a source sending the binding values to other bindings.
+    ///
+    /// A newly attached binding receives the current binding value, if any.
+    pub fn source(self) -> BindingSource<T> {
+        BindingSource(self.0, PhantomType::new())
+    }
+
+    pub fn add_target(
+        self,
+        state: &mut dyn State,
+        target: Box<dyn Target<T>>,
+        holder: Option<Box<dyn Holder>>
+    ) -> BindingTargetId<T> {
+        BindingBase::from(self).add_target(state, target, holder)
+    }
+}
+
+/// An additional binding target id, returned by the [`BindingBase::add_target`] method.
+#[derive(Educe)]
+#[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct BindingTargetId<T: Convenient> {
+    binding: Id<AnyBindingNode>,
+    target: Id<BindingOutputNode<T>>,
+}
+
+impl<T: Convenient> BindingTargetId<T> {
+    pub fn binding(self) -> BindingBase<T> { BindingBase(self.binding, PhantomType::new()) }
+
+    fn take(self, state: &mut dyn State) -> Option<BindingOutputNode<T>> {
+        let bindings: &mut Bindings = state.get_mut();
+        if !bindings.contains(self.binding) { return None; }
+        bindings.nodes[self.binding].downcast_mut::<T>().remove_output(self.target)
+    }
+
+    /// Removes the target from the binding, releasing the target holder.
+    ///
+    /// Returns `false` if the target is already removed, e.g. because the binding is dropped.
+    pub fn remove(self, state: &mut dyn State) -> bool {
+        if let Some(target) = self.take(state) {
+            target.holder.map(|x| x.release(state));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<T: Convenient> AnyHandler for BindingTargetId<T> {
+    /// Removes the target from the binding without releasing the target holder.
+    fn clear(&self, state: &mut dyn State) {
+        self.take(state);
+    }
+}
+
+/// A [`Source`] sending values computed by a binding to other bindings.
+///
+/// A binding can have any number of attached downstream bindings,
+/// which are notified with the same computed value.
+#[derive(Educe)]
+#[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct BindingSource<T: Convenient, C: SourceCache<T> = ValueCache<T>>(Id<AnyBindingNode>, PhantomType<(T, C)>);
+
+impl<T: Convenient, C: SourceCache<T>> Source for BindingSource<T, C> {
+    type Value = T;
+    type Cache = C;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<T>>) -> HandledSource {
+        let init = if C::KEEPS_VALUE { Binding(self.0, PhantomType::new()).get_value(state) } else { None };
+        subscribe(state, self.0, handler, init)
+    }
+}
+
+impl<T: Convenient> From<BindingBase<T>> for AnyBindingBase {
+    fn from(v: BindingBase<T>) -> AnyBindingBase {
+        AnyBindingBase(v.0)
+    }
+}
+
+const BINDING_NODE_SOURCES_MAX_SIZE: usize = 192;
+
+/// A binding sources storage. Sources exceeding [`BINDING_NODE_SOURCES_MAX_SIZE`] are boxed.
+#[cfg_attr(target_pointer_width="64", repr(C, align(8)))]
+#[cfg_attr(target_pointer_width="32", repr(C, align(4)))]
+#[cfg_attr(not(any(target_pointer_width="32", target_pointer_width="64")), repr(C, align(1)))]
+struct BindingNodeSourcesBuf([MaybeUninit<u8>; BINDING_NODE_SOURCES_MAX_SIZE]);
+
+impl BindingNodeSourcesBuf {
+    const fn is_inline<T>() -> bool {
+        size_of::<T>() <= BINDING_NODE_SOURCES_MAX_SIZE && align_of::<T>() <= align_of::<BindingNodeSourcesBuf>()
+    }
+
+    fn new<T>(sources: T) -> BindingNodeSourcesBuf {
+        let mut buf = BindingNodeSourcesBuf(unsafe { MaybeUninit::uninit().assume_init() });
+        if Self::is_inline::<T>() {
+            unsafe { ptr::write(buf.0.as_mut_ptr() as *mut T, sources); }
+        } else {
+            unsafe { ptr::write(buf.0.as_mut_ptr() as *mut *mut T, Box::into_raw(Box::new(sources))); }
+        }
+        buf
+    }
+
+    fn as_ptr<T>(&self) -> *const T {
+        if Self::is_inline::<T>() {
+            self.0.as_ptr() as _
+        } else {
+            unsafe { *(self.0.as_ptr() as *const *const T) }
+        }
+    }
+
+    fn as_mut_ptr<T>(&mut self) -> *mut T {
+        if Self::is_inline::<T>() {
+            self.0.as_mut_ptr() as _
+        } else {
+            unsafe { *(self.0.as_ptr() as *const *mut T) }
+        }
+    }
+
+    unsafe fn drop_in_place<T>(&mut self) {
+        if Self::is_inline::<T>() {
+            ptr::drop_in_place(self.as_mut_ptr::<T>());
+        } else {
+            drop(Box::from_raw(self.as_mut_ptr::<T>()));
+        }
+    }
+
+    /// Moves `f` into the `T` sources closure parameter, returned by `param`.
+    ///
+    /// The closure is placed in the buffer space left after inline sources, if it fits there,
+    /// and boxed otherwise. The sources should not be moved out of the buffer after that.
+    unsafe fn place_closure<T, F: 'static>(&mut self, param: impl FnOnce(&mut T) -> &mut Closure<F>, f: F) {
+        let param: *mut Closure<F> = param(&mut *self.as_mut_ptr::<T>());
+        let repr = if Self::is_inline::<T>() {
+            let tail = (self.0.as_mut_ptr() as *mut u8).add(size_of::<T>());
+            let offset = tail.align_offset(align_of::<F>());
+            let fits = align_of::<F>() <= align_of::<BindingNodeSourcesBuf>()
+                && size_of::<T>() + offset + size_of::<F>() <= BINDING_NODE_SOURCES_MAX_SIZE;
+            if fits {
+                let place = tail.add(offset);
+                ptr::write(place as *mut F, f);
+                ClosureRepr::Inline(place.offset_from(param as *mut u8), PhantomType::new())
+            } else {
+                ClosureRepr::Boxed(Box::new(f))
+            }
+        } else {
+            ClosureRepr::Boxed(Box::new(f))
+        };
+        (*param).0 = repr;
+    }
+}
+
+struct SubjectNodeSources<T: Convenient> {
+    last: Option<T>,
+}
+
+impl<T: Convenient> From<SubjectNodeSources<T>> for AnyBindingNodeSources<T> {
+    fn from(sources: SubjectNodeSources<T>) -> Self {
+        AnyBindingNodeSources {
+            buf: BindingNodeSourcesBuf::new(sources),
+            vtable: &SubjectNodeSources::<T>::VTABLE
+        }
+    }
+}
+
+impl<T: Convenient> SubjectNodeSources<T> {
+    const VTABLE: AnyBindingNodeSourcesVtable<T> = AnyBindingNodeSourcesVtable {
+        ty: TypeId::of::<Self>(),
+        kind: "Subject",
+        describe: describe_no_sources,
+        drop: Self::drop,
+        is_empty: Self::is_empty,
+        unhandle: Self::unhandle,
+        get_value: Self::get_value,
+        evaluate: evaluate_nothing,
+    };
+
+    unsafe fn drop(buf: &mut BindingNodeSourcesBuf) {
+        buf.drop_in_place::<Self>();
+    }
+
+    unsafe fn is_empty(_buf: &BindingNodeSourcesBuf) -> bool { true }
+
+    unsafe fn unhandle(_buf: &mut BindingNodeSourcesBuf, _state: &mut dyn State, _dropping_binding: AnyBindingBase) { }
+
+    unsafe fn get_value(buf: &BindingNodeSourcesBuf) -> Option<T> {
+        let this: &Self = &*buf.as_ptr();
+        this.last.clone()
+    }
+}
+
+/// A manually driven [`Source`].
+///
+/// A subject allows code having no dependency objects to send values to bindings
+/// with the [`push`](Subject::push) method.
+/// With the [`ValueCache`] caching strategy a subject keeps the last pushed value
+/// and sends it to newly attached bindings.
+///
+/// A subject lives in [`Bindings`] and should be dropped with the [`drop_self`](Subject::drop_self)
+/// method (or got a [`Holder`] with the [`set_holder`](Subject::set_holder) method).
+#[derive(Educe)]
+#[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Subject<T: Convenient, C: SourceCache<T> = ValueCache<T>>(Id<AnyBindingNode>, PhantomType<(T, C)>);
+
+impl<T: Convenient, C: SourceCache<T>> Subject<T, C> {
+    #[track_caller]
+    pub fn new(state: &mut dyn State) -> Self {
+        let location = caller_location();
+        let bindings: &mut Bindings = state.get_mut();
+        let id = bindings.nodes.insert(|id| {
+            let node: BindingNode<T> = BindingNode::new(SubjectNodeSources { last: None }.into());
+            let mut node: AnyBindingNode = node.into();
+            node.location = location;
+            (node, id)
+        });
+        bindings.register_in_scope(id);
+        Subject(id, PhantomType::new())
+    }
+
+    pub fn set_holder(self, state: &mut dyn State, holder: Box<dyn Holder>) {
+        BindingBase::from(self).set_holder(state, holder);
+    }
+
+    pub fn drop_self(self, state: &mut dyn State) {
+        AnyBindingBase::from(self).drop_self(state);
+    }
+
+    /// Returns the last pushed value, if the subject keeps it.
+    pub fn get_value(self, state: &dyn State) -> Option<T> {
+        let bindings: &Bindings = state.get();
+        let node = bindings.nodes[self.0].downcast_ref::<T>();
+        unsafe { (node.sources.vtable.get_value)(&node.sources.buf) }
+    }
+
+    /// Sends the value to all attached handlers.
+    pub fn push(self, state: &mut dyn State, value: T) {
+        let bindings: &mut Bindings = state.get_mut();
+        let enabled = bindings.nodes[self.0].enabled;
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        if C::KEEPS_VALUE {
+            node.sources.downcast_mut::<SubjectNodeSources<T>>().last = Some(value.clone());
+        }
+        if !enabled { return; }
+        let outputs = node.outputs(self.0);
+        Recorder::run_driven(state, |state| {
+            Bindings::run_propagation(state, |state| outputs.execute(state, value));
+        });
+    }
+}
+
+impl<T: Convenient, C: SourceCache<T>> From<Subject<T, C>> for BindingBase<T> {
+    fn from(v: Subject<T, C>) -> BindingBase<T> {
+        BindingBase(v.0, PhantomType::new())
+    }
+}
+
+impl<T: Convenient, C: SourceCache<T>> From<Subject<T, C>> for AnyBindingBase {
+    fn from(v: Subject<T, C>) -> AnyBindingBase {
+        AnyBindingBase(v.0)
+    }
+}
+
+#[derive(Debug)]
+struct SubscriberId<T: Convenient> {
+    node: Id<AnyBindingNode>,
+    subscriber: Id<BindingOutputNode<T>>,
+}
+
+impl<T: Convenient> HandlerId for SubscriberId<T> {
+    fn unhandle(&self, state: &mut dyn State, _dropping_binding: AnyBindingBase) {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.node].downcast_mut::<T>();
+        node.remove_output(self.subscriber);
+    }
+
+    fn describe(&self) -> SourceDescriptor { SourceDescriptor::Binding(AnyBindingBase(self.node)) }
+}
+
+fn subscribe<T: Convenient>(
+    state: &mut dyn State,
+    node_id: Id<AnyBindingNode>,
+    handler: Box<dyn Handler<T>>,
+    init: Option<T>,
+) -> HandledSource {
+    let bindings: &mut Bindings = state.get_mut();
+    let node = bindings.nodes[node_id].downcast_mut::<T>();
+    let handler: Rc<dyn Handler<T>> = handler.into();
+    let subscriber = node.add_output(BindingOutput::Handler(handler.clone()), None);
+    let init = init.map(|value| {
+        let init: Box<dyn FnOnce(&mut dyn State)> = Box::new(move |state: &mut dyn State| {
+            handler.execute(state, value);
+        });
+        init
+    });
+    HandledSource {
+        handler_id: Box::new(SubscriberId { node: node_id, subscriber }),
+        init
+    }
+}
+
+impl<T: Convenient, C: SourceCache<T>> Source for Subject<T, C> {
+    type Value = T;
+    type Cache = C;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<T>>) -> HandledSource {
+        let init = if C::KEEPS_VALUE { self.get_value(state) } else { None };
+        subscribe(state, self.0, handler, init)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum ScopeMember {
+    Binding(Id<AnyBindingNode>),
+    Scope(Id<ScopeNode>),
+}
+
+macro_attr! {
+    #[derive(Debug, Component!(alloc=&'static dyn Allocator))]
+    struct ScopeNode {
+        members: BTreeMap<u64, ScopeMember>,
+        location: Option<&'static Location<'static>>,
+    }
+}
+
+/// Binding scopes, kept apart from binding nodes.
+///
+/// Every registered member is indexed in `registrations`,
+/// so a member dropped independently leaves its scope immediately.
+struct Scopes {
+    arena: Arena<ScopeNode>,
+    current: Option<Id<ScopeNode>>,
+    registrations: BTreeMap<ScopeMember, (Id<ScopeNode>, u64)>,
+    seq: u64,
+}
+
+impl Scopes {
+    const fn new_in(alloc: &'static dyn Allocator) -> Self {
+        Scopes { arena: Arena::new_in(alloc), current: None, registrations: BTreeMap::new(), seq: 0 }
+    }
+
+    fn register(&mut self, scope: Id<ScopeNode>, member: ScopeMember) {
+        if !arena_contains(&self.arena, scope) { return; }
+        self.unregister(member);
+        let seq = self.seq;
+        self.seq += 1;
+        self.arena[scope].members.insert(seq, member);
+        self.registrations.insert(member, (scope, seq));
+    }
+
+    fn unregister(&mut self, member: ScopeMember) {
+        if let Some((scope, seq)) = self.registrations.remove(&member) {
+            if arena_contains(&self.arena, scope) {
+                self.arena[scope].members.remove(&seq);
+            }
+        }
+    }
+}
+
+/// A group of bindings dropped together.
+///
+/// Bindings (and nested scopes) created inside the [`run`](BindingScope::run) method callback,
+/// or registered with the [`add`](BindingScope::add) and [`add_scope`](BindingScope::add_scope) methods,
+/// are dropped by the scope [`drop_self`](BindingScope::drop_self) method, in reverse registration order.
+/// A registered binding can be dropped independently before its scope, leaving the scope.
+///
+/// A scope lives in [`Bindings`] (though not being a binding node), and should be dropped explicitly.
+#[derive(Educe)]
+#[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct BindingScope(Id<ScopeNode>);
+
+/// Restores the outer active scope when dropped, even if unwinding.
+struct ScopeGuard {
+    state: *mut dyn State,
+    outer: Option<Id<ScopeNode>>,
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        // SAFETY: see `BindingScope::run`.
+        let state = unsafe { &mut *self.state };
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.scopes.current = self.outer;
+    }
+}
+
+impl BindingScope {
+    /// Creates a new scope. If there is an active scope (see [`run`](BindingScope::run)),
+    /// the new scope is nested in it.
+    #[track_caller]
+    pub fn new(state: &mut dyn State) -> Self {
+        let location = caller_location();
+        let bindings: &mut Bindings = state.get_mut();
+        let scopes = &mut bindings.scopes;
+        let id = scopes.arena.insert(|id| (ScopeNode { members: BTreeMap::new(), location }, id));
+        if let Some(outer) = scopes.current {
+            scopes.register(outer, ScopeMember::Scope(id));
+        }
+        BindingScope(id)
+    }
+
+    /// Registers a binding created outside the [`run`](BindingScope::run) method with the scope.
+    ///
+    /// A binding registered with another scope is moved to this one.
+    pub fn add(self, state: &mut dyn State, binding: impl Into<AnyBindingBase>) {
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.scopes.register(self.0, ScopeMember::Binding(binding.into().0));
+    }
+
+    /// Nests a scope created outside the [`run`](BindingScope::run) method in this scope.
+    pub fn add_scope(self, state: &mut dyn State, scope: BindingScope) {
+        assert!(scope != self, "scope cannot contain itself");
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.scopes.register(self.0, ScopeMember::Scope(scope.0));
+    }
+
+    /// Calls `f`, registering all bindings created inside it with the scope.
+    ///
+    /// The previously active scope is restored even if `f` panics.
+    pub fn run<R>(self, state: &mut dyn State, f: impl FnOnce(&mut dyn State) -> R) -> R {
+        let bindings: &mut Bindings = state.get_mut();
+        let outer = bindings.scopes.current.replace(self.0);
+        let guard = ScopeGuard { state, outer };
+        // SAFETY: `state` is not used after the guard creation, so the reference passed to `f`
+        // is the only live access to the state until `f` returns or unwinds.
+        // The guard dereferences the pointer only in `drop`, after the reference is gone.
+        f(unsafe { &mut *guard.state })
+    }
+
+    pub fn drop_self(self, state: &mut dyn State) {
+        let bindings: &mut Bindings = state.get_mut();
+        let scopes = &mut bindings.scopes;
+        scopes.unregister(ScopeMember::Scope(self.0));
+        let scope = scopes.arena.remove(self.0);
+        for member in scope.members.into_values().rev() {
+            let bindings: &mut Bindings = state.get_mut();
+            if bindings.scopes.registrations.remove(&member).is_none() { continue; }
+            match member {
+                ScopeMember::Binding(binding) => AnyBindingBase(binding).drop_self(state),
+                ScopeMember::Scope(scope) => BindingScope(scope).drop_self(state),
+            }
+        }
+    }
+}
+
+macro_attr! {
+    #[derive(Component!(alloc=&'static dyn Allocator))]
+    struct OperatorNode(Box<dyn Any>);
+}
+
+/// Typed access to a stateful source operator state, kept in [`Bindings`] apart from binding nodes.
+struct OperatorState<X: 'static>(PhantomType<X>);
+
+impl<X: 'static> OperatorState<X> {
+    fn insert(state: &mut dyn State, operator_state: X) -> Id<OperatorNode> {
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.operators.insert(|id| (OperatorNode(Box::new(operator_state)), id))
+    }
+
+    fn contains(state: &dyn State, node: Id<OperatorNode>) -> bool {
+        let bindings: &Bindings = state.get();
+        arena_contains(&bindings.operators, node)
+    }
+
+    fn get(state: &dyn State, node: Id<OperatorNode>) -> &X {
+        let bindings: &Bindings = state.get();
+        bindings.operators[node].0.downcast_ref().unwrap()
+    }
+
+    fn get_mut(state: &mut dyn State, node: Id<OperatorNode>) -> &mut X {
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.operators[node].0.downcast_mut().unwrap()
+    }
+
+    fn remove(state: &mut dyn State, node: Id<OperatorNode>) -> X {
+        let bindings: &mut Bindings = state.get_mut();
+        *bindings.operators.remove(node).0.downcast().unwrap()
+    }
+}
+
+#[derive(Debug)]
+struct OperatorHandlerId {
+    node: Id<OperatorNode>,
+    handler_id: Box<dyn HandlerId>,
+}
+
+impl HandlerId for OperatorHandlerId {
+    fn unhandle(&self, state: &mut dyn State, dropping_binding: AnyBindingBase) {
+        let bindings: &mut Bindings = state.get_mut();
+        bindings.operators.remove(self.node);
+        self.handler_id.unhandle(state, dropping_binding);
+    }
+
+    fn describe(&self) -> SourceDescriptor { self.handler_id.describe() }
+}
+
+/// A [`Source`] transforming values with a function, see [`Source::map`].
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Map<S: Source, U: Convenient, F = fn(<S as Source>::Value) -> U> {
+    source: S,
+    #[educe(Debug(ignore))]
+    f: F,
+    phantom: PhantomType<U>,
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct MapHandler<T, U, F> {
+    handler: Box<dyn Handler<U>>,
+    node: Id<OperatorNode>,
+    phantom: PhantomType<(T, F)>,
+}
+
+impl<T: Convenient, U: Convenient, F: Fn(T) -> U + 'static> AnyHandler for MapHandler<T, U, F> {
+    fn clear(&self, state: &mut dyn State) {
+        OperatorState::<F>::remove(state, self.node);
+        self.handler.clone().into_any().clear(state);
+    }
+}
+
+impl<T: Convenient, U: Convenient, F: Fn(T) -> U + 'static> Handler<T> for MapHandler<T, U, F> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        let value = (OperatorState::<F>::get(state, self.node))(value);
+        self.handler.execute(state, value);
+    }
+}
+
+impl<
+    S: Source,
+    U: Convenient,
+    F: Fn(S::Value) -> U + Clone + 'static
+> Source for Map<S, U, F> where S::Cache: SourceCacheMap<S::Value, U> {
+    type Value = U;
+    type Cache = <S::Cache as SourceCacheMap<S::Value, U>>::Mapped;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<U>>) -> HandledSource {
+        let node = OperatorState::<F>::insert(state, self.f.clone());
+        let handler: MapHandler<S::Value, U, F> = MapHandler { handler, node, phantom: PhantomType::new() };
+        let source = self.source.handle(state, Box::new(handler));
+        HandledSource {
+            handler_id: Box::new(OperatorHandlerId { node, handler_id: source.handler_id }),
+            init: source.init
+        }
+    }
+}
+
+/// A [`Source`] skipping values not satisfying a predicate, see [`Source::filter`].
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Filter<S: Source, F = fn(&<S as Source>::Value) -> bool> {
+    source: S,
+    #[educe(Debug(ignore))]
+    pred: F,
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct FilterHandler<T, F> {
+    handler: Box<dyn Handler<T>>,
+    node: Id<OperatorNode>,
+    phantom: PhantomType<F>,
+}
+
+impl<T: Convenient, F: Fn(&T) -> bool + 'static> AnyHandler for FilterHandler<T, F> {
+    fn clear(&self, state: &mut dyn State) {
+        OperatorState::<F>::remove(state, self.node);
+        self.handler.clone().into_any().clear(state);
+    }
+}
+
+impl<T: Convenient, F: Fn(&T) -> bool + 'static> Handler<T> for FilterHandler<T, F> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        if (OperatorState::<F>::get(state, self.node))(&value) {
+            self.handler.execute(state, value);
+        }
+    }
+}
+
+impl<S: Source, F: Fn(&S::Value) -> bool + Clone + 'static> Source for Filter<S, F> {
+    type Value = S::Value;
+    type Cache = S::Cache;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<S::Value>>) -> HandledSource {
+        let node = OperatorState::<F>::insert(state, self.pred.clone());
+        let handler: FilterHandler<S::Value, F> = FilterHandler { handler, node, phantom: PhantomType::new() };
+        let source = self.source.handle(state, Box::new(handler));
+        HandledSource {
+            handler_id: Box::new(OperatorHandlerId { node, handler_id: source.handler_id }),
+            init: source.init
+        }
+    }
+}
+
+/// A [`Source`] skipping values equal to the previous one, see [`Source::distinct`].
+#[derive(Debug)]
+pub struct Distinct<S: Source> {
+    source: S,
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct DistinctHandler<T> {
+    handler: Box<dyn Handler<T>>,
+    node: Id<OperatorNode>,
+}
+
+impl<T: Convenient> AnyHandler for DistinctHandler<T> {
+    fn clear(&self, state: &mut dyn State) {
+        OperatorState::<Option<T>>::remove(state, self.node);
+        self.handler.clone().into_any().clear(state);
+    }
+}
+
+impl<T: Convenient> Handler<T> for DistinctHandler<T> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        let last = OperatorState::<Option<T>>::get_mut(state, self.node);
+        if last.as_ref() == Some(&value) { return; }
+        *last = Some(value.clone());
+        self.handler.execute(state, value);
+    }
+}
+
+impl<S: Source> Source for Distinct<S> {
+    type Value = S::Value;
+    type Cache = S::Cache;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<S::Value>>) -> HandledSource {
+        let node = OperatorState::<Option<S::Value>>::insert(state, None);
+        let source = self.source.handle(state, Box::new(DistinctHandler { handler, node }));
+        HandledSource {
+            handler_id: Box::new(OperatorHandlerId { node, handler_id: source.handler_id }),
+            init: source.init
+        }
+    }
+}
+
+/// A [`Source`] accumulating values, see [`Source::scan`].
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Scan<S: Source, A: Convenient, F = fn(A, <S as Source>::Value) -> A> {
+    source: S,
+    init: A,
+    #[educe(Debug(ignore))]
+    f: F,
+}
+
+struct ScanState<A, F> {
+    acc: A,
+    f: F,
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct ScanHandler<T, A, F> {
+    handler: Box<dyn Handler<A>>,
+    node: Id<OperatorNode>,
+    phantom: PhantomType<(T, F)>,
+}
+
+impl<T: Convenient, A: Convenient, F: Fn(A, T) -> A + 'static> AnyHandler for ScanHandler<T, A, F> {
+    fn clear(&self, state: &mut dyn State) {
+        OperatorState::<ScanState<A, F>>::remove(state, self.node);
+        self.handler.clone().into_any().clear(state);
+    }
+}
+
+impl<T: Convenient, A: Convenient, F: Fn(A, T) -> A + 'static> Handler<T> for ScanHandler<T, A, F> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        let scan = OperatorState::<ScanState<A, F>>::get_mut(state, self.node);
+        scan.acc = (scan.f)(scan.acc.clone(), value);
+        let acc = scan.acc.clone();
+        self.handler.execute(state, acc);
+    }
+}
+
+impl<S: Source, A: Convenient, F: Fn(A, S::Value) -> A + Clone + 'static> Source for Scan<S, A, F> {
+    type Value = A;
+    type Cache = ValueCache<A>;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<A>>) -> HandledSource {
+        let node = OperatorState::<ScanState<A, F>>::insert(state, ScanState {
+            acc: self.init.clone(),
+            f: self.f.clone()
+        });
+        let handler: ScanHandler<S::Value, A, F> = ScanHandler { handler, node, phantom: PhantomType::new() };
+        let source = self.source.handle(state, Box::new(handler));
+        HandledSource {
+            handler_id: Box::new(OperatorHandlerId { node, handler_id: source.handler_id }),
+            init: source.init
+        }
+    }
+}
+
+/// A [`Source`] sending values from two sources, see [`Source::merge`].
+#[derive(Debug)]
+pub struct Merge<S1: Source, S2: Source> {
+    source_1: S1,
+    source_2: S2,
+}
+
+type MergeState = [Option<Box<dyn HandlerId>>; 2];
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct MergeHandler<T> {
+    handler: Box<dyn Handler<T>>,
+    node: Id<OperatorNode>,
+    index: usize,
+}
+
+impl<T: Convenient> AnyHandler for MergeHandler<T> {
+    fn clear(&self, state: &mut dyn State) {
+        let handler_ids = OperatorState::<MergeState>::get_mut(state, self.node);
+        handler_ids[self.index] = None;
+        if handler_ids.iter().all(|x| x.is_none()) {
+            OperatorState::<MergeState>::remove(state, self.node);
+            self.handler.clone().into_any().clear(state);
+        }
+    }
+}
+
+impl<T: Convenient> Handler<T> for MergeHandler<T> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        self.handler.execute(state, value);
+    }
+}
+
+#[derive(Debug)]
+struct MergeHandlerId {
+    node: Id<OperatorNode>,
+    description: SourceDescriptor,
+}
+
+impl HandlerId for MergeHandlerId {
+    fn unhandle(&self, state: &mut dyn State, dropping_binding: AnyBindingBase) {
+        let handler_ids = OperatorState::<MergeState>::remove(state, self.node);
+        for handler_id in handler_ids.iter().flatten() {
+            handler_id.unhandle(state, dropping_binding);
+        }
+    }
+
+    fn describe(&self) -> SourceDescriptor { self.description.clone() }
+}
+
+impl<S1: Source, S2: Source<Value=S1::Value, Cache=S1::Cache>> Source for Merge<S1, S2> {
+    type Value = S1::Value;
+    type Cache = S1::Cache;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<S1::Value>>) -> HandledSource {
+        let node = OperatorState::<MergeState>::insert(state, Default::default());
+        let source_1 = self.source_1.handle(state, Box::new(MergeHandler { handler: handler.clone(), node, index: 0 }));
+        let source_2 = self.source_2.handle(state, Box::new(MergeHandler { handler, node, index: 1 }));
+        let description = SourceDescriptor::Merge(Vec::from([source_1.handler_id.describe(), source_2.handler_id.describe()]));
+        *OperatorState::<MergeState>::get_mut(state, node) = [Some(source_1.handler_id), Some(source_2.handler_id)];
+        let init = match (source_1.init, source_2.init) {
+            (None, None) => None,
+            (init_1, init_2) => {
+                let init: Box<dyn FnOnce(&mut dyn State)> = Box::new(move |state: &mut dyn State| {
+                    init_1.map(|x| x(state));
+                    init_2.map(|x| x(state));
+                });
+                Some(init)
+            },
+        };
+        HandledSource {
+            handler_id: Box::new(MergeHandlerId { node, description }),
+            init
+        }
+    }
+}
+
+/// A [`Source`] following an inner source selected by the outer source value, see [`Source::switch`].
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Switch<S: Source, I: Source, F = fn(<S as Source>::Value) -> Option<I>> {
+    source: S,
+    default: I::Value,
+    #[educe(Debug(ignore))]
+    f: F,
+}
+
+struct SwitchState<T, F> {
+    inner: Option<Box<dyn HandlerId>>,
+    default: T,
+    f: F,
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone(bound="T: Convenient"))]
+struct SwitchHandler<T, I: Source, F> {
+    handler: Box<dyn Handler<I::Value>>,
+    node: Id<OperatorNode>,
+    phantom: PhantomType<(T, F)>,
+}
+
+impl<T: Convenient, I: Source + 'static, F: Fn(T) -> Option<I> + 'static> AnyHandler for SwitchHandler<T, I, F> {
+    fn clear(&self, state: &mut dyn State) {
+        let inner = OperatorState::<SwitchState<I::Value, F>>::remove(state, self.node).inner;
+        inner.map(|x| x.unhandle(state, AnyBindingBase::none()));
+        self.handler.clone().into_any().clear(state);
+    }
+}
+
+impl<T: Convenient, I: Source + 'static, F: Fn(T) -> Option<I> + 'static> Handler<T> for SwitchHandler<T, I, F> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        let switch = OperatorState::<SwitchState<I::Value, F>>::get_mut(state, self.node);
+        let inner = switch.inner.take();
+        inner.map(|x| x.unhandle(state, AnyBindingBase::none()));
+        let switch = OperatorState::<SwitchState<I::Value, F>>::get(state, self.node);
+        if let Some(source) = (switch.f)(value) {
+            let handler: SwitchInnerHandler<I::Value, F> = SwitchInnerHandler {
+                handler: self.handler.clone(),
+                node: self.node,
+                phantom: PhantomType::new()
+            };
+            let source = source.handle(state, Box::new(handler));
+            OperatorState::<SwitchState<I::Value, F>>::get_mut(state, self.node).inner = Some(source.handler_id);
+            source.init.map(|x| x(state));
+        } else {
+            let default = switch.default.clone();
+            self.handler.execute(state, default);
+        }
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct SwitchInnerHandler<T, F> {
+    handler: Box<dyn Handler<T>>,
+    node: Id<OperatorNode>,
+    phantom: PhantomType<F>,
+}
+
+impl<T: Convenient, F: 'static> AnyHandler for SwitchInnerHandler<T, F> {
+    fn clear(&self, state: &mut dyn State) {
+        if OperatorState::<SwitchState<T, F>>::contains(state, self.node) {
+            OperatorState::<SwitchState<T, F>>::get_mut(state, self.node).inner = None;
+        }
+    }
+}
+
+impl<T: Convenient, F: 'static> Handler<T> for SwitchInnerHandler<T, F> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        self.handler.execute(state, value);
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug)]
+struct SwitchHandlerId<T, F> {
+    node: Id<OperatorNode>,
+    handler_id: Box<dyn HandlerId>,
+    phantom: PhantomType<(T, F)>,
+}
+
+impl<T: Convenient, F: 'static> HandlerId for SwitchHandlerId<T, F> {
+    fn unhandle(&self, state: &mut dyn State, dropping_binding: AnyBindingBase) {
+        let inner = OperatorState::<SwitchState<T, F>>::remove(state, self.node).inner;
+        inner.map(|x| x.unhandle(state, dropping_binding));
+        self.handler_id.unhandle(state, dropping_binding);
+    }
+
+    fn describe(&self) -> SourceDescriptor { self.handler_id.describe() }
+}
+
+impl<
+    S: Source,
+    I: Source + 'static,
+    F: Fn(S::Value) -> Option<I> + Clone + 'static
+> Source for Switch<S, I, F> {
+    type Value = I::Value;
+    type Cache = I::Cache;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<I::Value>>) -> HandledSource {
+        let node = OperatorState::<SwitchState<I::Value, F>>::insert(state, SwitchState {
+            inner: None,
+            default: self.default.clone(),
+            f: self.f.clone()
+        });
+        let handler: SwitchHandler<S::Value, I, F> = SwitchHandler { handler, node, phantom: PhantomType::new() };
+        let source = self.source.handle(state, Box::new(handler));
+        HandledSource {
+            handler_id: Box::new(SwitchHandlerId::<I::Value, F> {
+                node,
+                handler_id: source.handler_id,
+                phantom: PhantomType::new()
+            }),
+            init: source.init
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum TimedKind {
+    Debounce,
+    Throttle,
+    Delay,
+    Sample,
+}
+
+/// A [`Source`] with a time-based operator applied,
+/// see [`Source::debounce`], [`Source::throttle`], [`Source::delay`], and [`Source::sample`].
+///
+/// Requires the [`Clock`] state part.
+#[derive(Debug)]
+pub struct Timed<S: Source> {
+    source: S,
+    kind: TimedKind,
+    duration: u64,
+}
+
+struct TimedState<T> {
+    timers: Vec<TimerId>,
+    value: Option<T>,
+    last: Option<u64>,
+}
+
+impl<T> Default for TimedState<T> {
+    fn default() -> Self { TimedState { timers: Vec::new(), value: None, last: None } }
+}
+
+fn timed_clock(state: &mut dyn State) -> &mut Clock {
+    state.get_mut_raw(TypeId::of::<Clock>())
+        .expect("time-based binding operators require the Clock state part")
+        .downcast_mut::<Clock>().unwrap()
+}
+
+impl<T: Convenient> TimedState<T> {
+    fn remove(state: &mut dyn State, node: Id<OperatorNode>) {
+        let timers = OperatorState::<Self>::remove(state, node).timers;
+        let clock = timed_clock(state);
+        for timer in timers {
+            clock.cancel(timer);
+        }
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct TimedHandler<T> {
+    handler: Box<dyn Handler<T>>,
+    node: Id<OperatorNode>,
+    kind: TimedKind,
+    duration: u64,
+}
+
+impl<T: Convenient> TimedHandler<T> {
+    fn schedule(&self, state: &mut dyn State, value: Option<T>) {
+        let handler = self.handler.clone();
+        let node = self.node;
+        let clock = timed_clock(state);
+        let timer = clock.schedule(self.duration, Box::new(move |state: &mut dyn State, timer| {
+            let timed = OperatorState::<TimedState<T>>::get_mut(state, node);
+            timed.timers.retain(|&x| x != timer);
+            if let Some(value) = value.or_else(|| timed.value.take()) {
+                handler.execute(state, value);
+            }
+        }));
+        OperatorState::<TimedState<T>>::get_mut(state, self.node).timers.push(timer);
+    }
+}
+
+impl<T: Convenient> AnyHandler for TimedHandler<T> {
+    fn clear(&self, state: &mut dyn State) {
+        TimedState::<T>::remove(state, self.node);
+        self.handler.clone().into_any().clear(state);
+    }
+}
+
+impl<T: Convenient> Handler<T> for TimedHandler<T> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
+    }
+
+    fn execute(&self, state: &mut dyn State, value: T) {
+        match self.kind {
+            TimedKind::Debounce => {
+                let timers = take(&mut OperatorState::<TimedState<T>>::get_mut(state, self.node).timers);
+                let clock = timed_clock(state);
+                for timer in timers {
+                    clock.cancel(timer);
+                }
+                self.schedule(state, Some(value));
+            },
+            TimedKind::Throttle => {
+                let now = timed_clock(state).now();
+                let timed = OperatorState::<TimedState<T>>::get_mut(state, self.node);
+                if timed.last.map_or(true, |last| now >= last.saturating_add(self.duration)) {
+                    timed.last = Some(now);
+                    self.handler.execute(state, value);
+                }
+            },
+            TimedKind::Delay => self.schedule(state, Some(value)),
+            TimedKind::Sample => {
+                let timed = OperatorState::<TimedState<T>>::get_mut(state, self.node);
+                if timed.value.replace(value).is_none() {
+                    self.schedule(state, None);
+                }
+            },
+        }
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug)]
+struct TimedHandlerId<T> {
+    node: Id<OperatorNode>,
+    handler_id: Box<dyn HandlerId>,
+    phantom: PhantomType<T>,
+}
+
+impl<T: Convenient> HandlerId for TimedHandlerId<T> {
+    fn unhandle(&self, state: &mut dyn State, dropping_binding: AnyBindingBase) {
+        TimedState::<T>::remove(state, self.node);
+        self.handler_id.unhandle(state, dropping_binding);
+    }
+
+    fn describe(&self) -> SourceDescriptor { self.handler_id.describe() }
+}
+
+impl<S: Source> Source for Timed<S> {
+    type Value = S::Value;
+    type Cache = S::Cache;
+
+    fn handle(&self, state: &mut dyn State, handler: Box<dyn Handler<S::Value>>) -> HandledSource {
+        timed_clock(state);
+        let node = OperatorState::<TimedState<S::Value>>::insert(state, TimedState::default());
+        let source = self.source.handle(state, Box::new(TimedHandler {
+            handler,
+            node,
+            kind: self.kind,
+            duration: self.duration
+        }));
+        HandledSource {
+            handler_id: Box::new(TimedHandlerId::<S::Value> { node, handler_id: source.handler_id, phantom: PhantomType::new() }),
+            init: source.init
+        }
+    }
+}
+
+macro_attr! {
+    #[derive(Educe, Component!(class=BindingVecSourceComponent))]
+    #[educe(Debug)]
+    struct BindingVecSource<S: Source> {
+        handler_id: Option<Box<dyn HandlerId>>,
+        cache: S::Cache,
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug(bound="P: Debug"))]
+struct BindingVecNodeSources<P, S: Source + 'static, T: Convenient> {
+    param: P,
+    sources: Arena<BindingVecSource<S>>,
+    /// Source ids in the insertion order.
+    order: Vec<Id<BindingVecSource<S>>>,
+    /// A buffer reused by evaluations to collect source values.
+    #[educe(Debug(ignore))]
+    values: Cell<Vec<<S::Cache as SourceCache<S::Value>>::Value>>,
+    #[educe(Debug(ignore))]
+    filter_map: FilterMap<
+        fn(P, &[<S::Cache as SourceCache<S::Value>>::Value]) -> Option<T>,
+        fn(&P, &[<S::Cache as SourceCache<S::Value>>::Value]) -> Option<T>
+    >,
+}
+
+impl<P: Clone + 'static, S: Source + 'static, T: Convenient> From<BindingVecNodeSources<P, S, T>> for AnyBindingNodeSources<T> {
+    fn from(sources: BindingVecNodeSources<P, S, T>) -> Self {
+        AnyBindingNodeSources {
+            buf: BindingNodeSourcesBuf::new(sources),
+            vtable: &BindingVecNodeSources::<P, S, T>::VTABLE
+        }
+    }
+}
+
+impl<P: Clone + 'static, S: Source + 'static, T: Convenient> BindingVecNodeSources<P, S, T> {
+    const VTABLE: AnyBindingNodeSourcesVtable<T> = AnyBindingNodeSourcesVtable {
+        ty: TypeId::of::<Self>(),
+        kind: "BindingVec",
+        describe: Self::describe,
+        drop: Self::drop,
+        is_empty: Self::is_empty,
+        unhandle: Self::unhandle,
+        get_value: Self::get_value,
+        evaluate: Self::evaluate,
+    };
+
+    unsafe fn drop(buf: &mut BindingNodeSourcesBuf) {
+        buf.drop_in_place::<Self>();
+    }
+
+    unsafe fn is_empty(buf: &BindingNodeSourcesBuf) -> bool {
+        let this: &Self = &*buf.as_ptr();
+        this.sources.items().is_empty()
+    }
+
+    fn evaluate(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+        let bindings: &Bindings = state.get();
+        let node = bindings.nodes[binding].downcast_ref::<T>();
+        let sources = node.sources.downcast_ref::<BindingVecNodeSources<P, S, T>>();
+        if let Some(value) = sources.filter_map(None) {
+            let outputs = node.outputs(binding);
+            outputs.execute(state, value);
+        }
+    }
+
+    unsafe fn describe(buf: &BindingNodeSourcesBuf) -> Vec<SourceDescriptor> {
+        let this: &Self = &*buf.as_ptr();
+        this.order.iter().filter_map(|&id| this.sources[id].handler_id.as_ref()).map(|x| x.describe()).collect()
+    }
+
+    unsafe fn unhandle(buf: &mut BindingNodeSourcesBuf, state: &mut dyn State, dropping_binding: AnyBindingBase) {
+        let this: &mut Self = &mut *buf.as_mut_ptr();
+        this.order.clear();
+        for source in take(&mut this.sources).into_items().into_values() {
+            source.handler_id.map(|x| x.unhandle(state, dropping_binding));
+        }
     }
 
-    pub fn set_target_fn<Context: Clone + 'static>(
-        self,
-        state: &mut dyn State,
-        context: Context,
-        execute: fn(state: &mut dyn State, context: Context, value: T)
-    ) {
-        self.set_target(state, Box::new(FnTarget { context, execute }));
+    unsafe fn get_value(buf: &BindingNodeSourcesBuf) -> Option<T> {
+        let this: &Self = &*buf.as_ptr();
+        this.filter_map(None)
     }
 
-    pub fn drop_self(self, state: &mut dyn State) {
-        AnyBindingBase::from(self).drop_self(state);
+    fn filter_map(&self, current: Option<(Id<BindingVecSource<S>>, S::Value)>) -> Option<T> {
+        let mut values = self.values.take();
+        let mut complete = true;
+        for &id in &self.order {
+            let current = current.as_ref().filter(|x| x.0 == id).map(|x| x.1.clone());
+            if let Some(value) = self.sources[id].cache.get(current) {
+                values.push(value);
+            } else {
+                complete = false;
+                break;
+            }
+        }
+        let res = if complete {
+            match self.filter_map {
+                FilterMap::Owned(filter_map) => filter_map(self.param.clone(), &values),
+                FilterMap::Ref(filter_map) => filter_map(&self.param, &values),
+            }
+        } else {
+            None
+        };
+        values.clear();
+        self.values.set(values);
+        res
     }
 }
 
-macro_attr! {
-    #[derive(Educe, NewtypeComponentId!)]
-    #[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
-    pub struct Binding<T: Convenient>(Id<AnyBindingNode>, PhantomType<T>);
-}
+/// A binding with a variable number of sources of the same type.
+///
+/// Sources can be added and removed at any moment.
+/// The `filter_map` function gets values from all sources as a slice
+/// in the sources insertion order (see [`add_source`](BindingVec::add_source)).
+/// As with fixed arity bindings, nothing is computed until every source has a value.
+#[derive(Educe)]
+#[educe(Debug, Eq, PartialEq, Copy, Clone, Ord, PartialOrd, Hash)]
+pub struct BindingVec<P, S: Source, T: Convenient>(Id<AnyBindingNode>, PhantomType<(P, S, T)>);
 
-impl<T: Convenient> From<Binding<T>> for BindingBase<T> {
-    fn from(binding: Binding<T>) -> Self {
-        BindingBase(binding.0, PhantomType::new())
+impl<P: Clone + 'static, S: Source + 'static, T: Convenient> BindingVec<P, S, T> {
+    #[track_caller]
+    pub fn new(
+        state: &mut dyn State,
+        param: P,
+        filter_map: fn(P, &[<S::Cache as SourceCache<S::Value>>::Value]) -> Option<T>,
+    ) -> Self {
+        Self::new_raw(state, param, FilterMap::Owned(filter_map))
     }
-}
 
-impl<T: Convenient> From<Binding<T>> for AnyBindingBase {
-    fn from(v: Binding<T>) -> AnyBindingBase {
-        AnyBindingBase(v.0)
+    #[track_caller]
+    fn new_raw(
+        state: &mut dyn State,
+        param: P,
+        filter_map: FilterMap<
+            fn(P, &[<S::Cache as SourceCache<S::Value>>::Value]) -> Option<T>,
+            fn(&P, &[<S::Cache as SourceCache<S::Value>>::Value]) -> Option<T>
+        >,
+    ) -> Self {
+        let location = caller_location();
+        let bindings: &mut Bindings = state.get_mut();
+        let id = bindings.nodes.insert(|id| {
+            let sources: BindingVecNodeSources<P, S, T> = BindingVecNodeSources {
+                param,
+                sources: Arena::new(),
+                order: Vec::new(),
+                values: Cell::new(Vec::new()),
+                filter_map,
+            };
+            let node: BindingNode<T> = BindingNode::new(sources.into());
+            let mut node: AnyBindingNode = node.into();
+            node.location = location;
+            (node, id)
+        });
+        bindings.register_in_scope(id);
+        BindingVec(id, PhantomType::new())
     }
-}
 
-impl<T: Convenient> Binding<T> {
     pub fn set_target(self, state: &mut dyn State, target: Box<dyn Target<T>>) {
         BindingBase::from(self).set_target(state, target);
     }
@@ -495,6 +3026,22 @@ impl<T: Convenient> Binding<T> {
         BindingBase::from(self).set_holder(state, holder);
     }
 
+    pub fn set_schedule(self, state: &mut dyn State, schedule: BindingSchedule) {
+        BindingBase::from(self).set_schedule(state, schedule);
+    }
+
+    pub fn is_enabled(self, state: &dyn State) -> bool {
+        BindingBase::from(self).is_enabled(state)
+    }
+
+    pub fn set_enabled(self, state: &mut dyn State, enabled: bool) {
+        BindingBase::from(self).set_enabled(state, enabled);
+    }
+
+    pub fn resume(self, state: &mut dyn State) {
+        BindingBase::from(self).resume(state);
+    }
+
     pub fn dispatch<Context: Clone + 'static>(
         self,
         state: &mut dyn State,
@@ -513,48 +3060,181 @@ impl<T: Convenient> Binding<T> {
         BindingBase::from(self).set_target_fn(state, context, execute);
     }
 
+    pub fn dispatch_closure(
+        self,
+        state: &mut dyn State,
+        execute: impl Fn(&mut dyn State, T) -> Re<!> + Clone + 'static
+    ) {
+        BindingBase::from(self).dispatch_closure(state, execute);
+    }
+
+    pub fn set_target_closure(
+        self,
+        state: &mut dyn State,
+        execute: impl Fn(&mut dyn State, T) + Clone + 'static
+    ) {
+        BindingBase::from(self).set_target_closure(state, execute);
+    }
+
     pub fn drop_self(self, state: &mut dyn State) {
         AnyBindingBase::from(self).drop_self(state);
     }
 
     pub fn get_value(self, state: &dyn State) -> Option<T> {
+        Binding::from(self).get_value(state)
+    }
+
+    pub fn add_target(
+        self,
+        state: &mut dyn State,
+        target: Box<dyn Target<T>>,
+        holder: Option<Box<dyn Holder>>
+    ) -> BindingTargetId<T> {
+        Binding::from(self).add_target(state, target, holder)
+    }
+
+    pub fn source(self) -> BindingSource<T> {
+        Binding::from(self).source()
+    }
+
+    pub fn add_source(self, state: &mut dyn State, source: &mut S) -> BindingVecSourceId<S> {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        let sources = node.sources.downcast_mut::<BindingVecNodeSources<P, S, T>>();
+        let source_id = sources.sources.insert(|id| (BindingVecSource { handler_id: None, cache: S::Cache::default() }, id));
+        sources.order.push(source_id);
+        let handler: BindingVecSourceHandler<P, S, T> = BindingVecSourceHandler {
+            binding: self.0,
+            source: source_id,
+            phantom: PhantomType::new()
+        };
+        let source = source.handle(state, Box::new(handler));
+        #[cfg(debug_assertions)]
+        if let Some(cycle) = find_cycle(state, self.0, &*source.handler_id) {
+            let bindings: &mut Bindings = state.get_mut();
+            let node = bindings.nodes[self.0].downcast_mut::<T>();
+            let sources = node.sources.downcast_mut::<BindingVecNodeSources<P, S, T>>();
+            sources.sources.remove(source_id);
+            sources.order.pop();
+            reject_cycle(state, self.0, &*source.handler_id, cycle);
+        }
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        let sources = node.sources.downcast_mut::<BindingVecNodeSources<P, S, T>>();
+        sources.sources[source_id].handler_id = Some(source.handler_id);
+        bindings.link(self.0);
+        source.init.map(|x| x(state));
+        BindingVecSourceId { binding: self.0, source: source_id }
+    }
+
+    /// Detaches the source from the binding and recomputes the binding value
+    /// from the remaining sources.
+    ///
+    /// Returns `false` if the source id does not belong to the binding,
+    /// or the source (or the binding itself) is already removed.
+    pub fn remove_source(self, state: &mut dyn State, source: BindingVecSourceId<S>) -> bool {
+        if source.binding != self.0 { return false; }
+        let bindings: &mut Bindings = state.get_mut();
+        if !bindings.contains(self.0) { return false; }
+        let node = bindings.nodes[self.0].downcast_mut::<T>();
+        let sources = node.sources.downcast_mut::<BindingVecNodeSources<P, S, T>>();
+        if !arena_contains(&sources.sources, source.source) { return false; }
+        let handler_id = sources.sources.remove(source.source).handler_id;
+        sources.order.retain(|&x| x != source.source);
+        handler_id.map(|x| x.unhandle(state, self.into()));
         let bindings: &Bindings = state.get();
-        let node = bindings.0[self.0].downcast_ref::<T>();
-        unsafe { (node.sources.vtable.get_value)(&node.sources.buf) }
+        if !bindings.nodes[self.0].enabled { return true; }
+        let schedule = bindings.nodes[self.0].downcast_ref::<T>().schedule;
+        if schedule != BindingSchedule::Immediate {
+            enqueue(state, self.0, schedule);
+        } else {
+            BindingVecNodeSources::<P, S, T>::evaluate(state, self.0);
+        }
+        true
     }
 }
 
-impl<T: Convenient> From<BindingBase<T>> for AnyBindingBase {
-    fn from(v: BindingBase<T>) -> AnyBindingBase {
+impl<
+    F: Fn(&[<S::Cache as SourceCache<S::Value>>::Value]) -> Option<T> + Clone + 'static,
+    S: Source + 'static,
+    T: Convenient
+> BindingVec<Closure<F>, S, T> {
+    /// Creates a binding with the closure `filter_map` function.
+    #[track_caller]
+    pub fn new_closure(state: &mut dyn State, filter_map: F) -> Self {
+        let binding = Self::new_raw(state, Closure::empty(), FilterMap::Ref(|f, values| (f.get())(values)));
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[binding.0].downcast_mut::<T>();
+        node.sources.place_closure(|sources: &mut BindingVecNodeSources<Closure<F>, S, T>| &mut sources.param, filter_map);
+        binding
+    }
+}
+
+impl<P, S: Source, T: Convenient> From<BindingVec<P, S, T>> for Binding<T> {
+    fn from(v: BindingVec<P, S, T>) -> Binding<T> {
+        Binding(v.0, PhantomType::new())
+    }
+}
+
+impl<P, S: Source, T: Convenient> From<BindingVec<P, S, T>> for BindingBase<T> {
+    fn from(v: BindingVec<P, S, T>) -> BindingBase<T> {
+        BindingBase(v.0, PhantomType::new())
+    }
+}
+
+impl<P, S: Source, T: Convenient> From<BindingVec<P, S, T>> for AnyBindingBase {
+    fn from(v: BindingVec<P, S, T>) -> AnyBindingBase {
         AnyBindingBase(v.0)
     }
 }
 
-const BINDING_NODE_SOURCES_MAX_SIZE: usize = 192;
+/// A [`BindingVec`] source id, returned by the [`BindingVec::add_source`] method.
+#[derive(Educe)]
+#[educe(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct BindingVecSourceId<S: Source> {
+    binding: Id<AnyBindingNode>,
+    source: Id<BindingVecSource<S>>,
+}
 
-#[cfg_attr(target_pointer_width="64", repr(C, align(8)))]
-#[cfg_attr(target_pointer_width="32", repr(C, align(4)))]
-#[cfg_attr(not(any(target_pointer_width="32", target_pointer_width="64")), repr(C, align(1)))]
-struct BindingNodeSourcesBuf([MaybeUninit<u8>; BINDING_NODE_SOURCES_MAX_SIZE]);
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct BindingVecSourceHandler<P, S: Source, T: Convenient> {
+    binding: Id<AnyBindingNode>,
+    source: Id<BindingVecSource<S>>,
+    phantom: PhantomType<(P, S, T)>
+}
 
-impl BindingNodeSourcesBuf {
-    fn new<T>(sources: T) -> BindingNodeSourcesBuf {
-        assert!(
-            size_of::<T>() <= BINDING_NODE_SOURCES_MAX_SIZE,
-            "{} > BINDING_NODE_SOURCES_MAX_SIZE", size_of::<T>()
-        );
-        assert!(align_of::<T>() <= align_of::<BindingNodeSourcesBuf>());
-        let mut buf = BindingNodeSourcesBuf(unsafe { MaybeUninit::uninit().assume_init() });
-        unsafe { ptr::write(buf.as_mut_ptr(), sources); }
-        buf
+impl<P: Clone + 'static, S: Source + 'static, T: Convenient> AnyHandler for BindingVecSourceHandler<P, S, T> {
+    fn clear(&self, state: &mut dyn State) {
+        let bindings: &mut Bindings = state.get_mut();
+        let node = bindings.nodes[self.binding].downcast_mut::<T>();
+        let sources = node.sources.downcast_mut::<BindingVecNodeSources<P, S, T>>();
+        sources.sources.remove(self.source);
+        sources.order.retain(|&x| x != self.source);
     }
+}
 
-    fn as_ptr<T>(&self) -> *const T {
-        self.0.as_ptr() as _
+impl<P: Clone + 'static, S: Source + 'static, T: Convenient> Handler<S::Value> for BindingVecSourceHandler<P, S, T> {
+    fn into_any(self: Box<Self>) -> Box<dyn AnyHandler> {
+        self
     }
 
-    fn as_mut_ptr<T>(&mut self) -> *mut T {
-        self.0.as_mut_ptr() as _
+    fn execute(&self, state: &mut dyn State, value: S::Value) {
+        let bindings: &mut Bindings = state.get_mut();
+        let enabled = bindings.nodes[self.binding].enabled;
+        let node = bindings.nodes[self.binding].downcast_mut::<T>();
+        let schedule = node.schedule;
+        let sources = node.sources.downcast_mut::<BindingVecNodeSources<P, S, T>>();
+        sources.sources[self.source].cache.update(value.clone());
+        if !enabled { return; }
+        if schedule != BindingSchedule::Immediate {
+            enqueue(state, self.binding, schedule);
+            return;
+        }
+        if let Some(value) = sources.filter_map(Some((self.source, value))) {
+            let outputs = node.outputs(self.binding);
+            outputs.execute(state, value);
+        }
     }
 }
 
@@ -615,15 +3295,17 @@ macro_rules! binding_n {
             > [< BindingExt $n NodeSources >] <P, $( [< S $i >] , )* T> {
                 const VTABLE: AnyBindingNodeSourcesVtable<T> = AnyBindingNodeSourcesVtable {
                     ty: TypeId::of::<Self>(),
+                    kind: concat!("BindingExt", $n),
+                    describe: Self::describe,
                     drop: Self::drop,
                     is_empty: Self::is_empty,
                     unhandle: Self::unhandle,
                     get_value: Self::get_value,
+                    evaluate: Self::evaluate,
                 };
 
                 unsafe fn drop(buf: &mut BindingNodeSourcesBuf) {
-                    let this: *mut Self = buf.as_mut_ptr();
-                    ptr::drop_in_place(this);
+                    buf.drop_in_place::<Self>();
                 }
 
                 #[allow(unused_variables)]
@@ -637,6 +3319,18 @@ macro_rules! binding_n {
                     true
                 }
 
+                #[allow(unused_variables, unused_mut)]
+                unsafe fn describe(buf: &BindingNodeSourcesBuf) -> Vec<SourceDescriptor> {
+                    let this: &Self = &*buf.as_ptr();
+                    let mut sources = Vec::new();
+                    $(
+                        if let Some(source) = this. [< source_ $i >] .as_ref() {
+                            sources.push(source.0.describe());
+                        }
+                    )*
+                    sources
+                }
+
                 #[allow(unused_variables)]
                 unsafe fn unhandle(buf: &mut BindingNodeSourcesBuf, state: &mut dyn State, dropping_binding: AnyBindingBase) {
                     let this: &mut Self = &mut *buf.as_mut_ptr();
@@ -647,8 +3341,33 @@ macro_rules! binding_n {
                     )*
                 }
 
-                unsafe fn get_value(_buf: &BindingNodeSourcesBuf) -> Option<T> {
-                    unreachable!()
+                unsafe fn get_value(_buf: &BindingNodeSourcesBuf) -> Option<T> { None }
+
+                fn evaluate(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+                    let bindings: &Bindings = state.get();
+                    let node = bindings.nodes[binding].downcast_ref::<T>();
+                    let sources = node.sources.downcast_ref::<Self>();
+                    $(
+                        let [< value_ $i >] ;
+                        if let Some(source) = sources. [< source_ $i >] .as_ref() {
+                            if let Some(source) = source.1.get(None) {
+                                [< value_ $i >] = source;
+                            } else {
+                                return;
+                            }
+                        } else {
+                            return;
+                        }
+                    )*
+                    let dispatch = sources.dispatch;
+                    let outputs = node.outputs(binding);
+                    let param = Param {
+                        id: binding.into_raw(),
+                        descriptor: & < [< BindingExt $n >] <P, $( [< S $i >] ,)* T> > ::PARAM_DESCRIPTOR
+                    };
+                    if let Re(Some(value)) = dispatch(state, param, $( [< value_ $i >] ),*) {
+                        outputs.execute(state, value);
+                    }
                 }
             }
 
@@ -666,6 +3385,7 @@ macro_rules! binding_n {
                 $( [< S $i >] : Source + 'static, )*
                 T: Convenient
             > [< BindingExt $n >] <P, $( [< S $i >] , )* T> {
+                #[track_caller]
                 pub fn new(
                     state: &mut dyn State,
                     param: P,
@@ -675,8 +3395,9 @@ macro_rules! binding_n {
                         $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),*
                     ) -> Re<T>,
                 ) -> Self {
+                    let location = caller_location();
                     let bindings: &mut Bindings = state.get_mut();
-                    let id = bindings.0.insert(|id| {
+                    let id = bindings.nodes.insert(|id| {
                         let sources: [< BindingExt $n NodeSources >] <P, $( [< S $i >] ,)* T> = [< BindingExt $n NodeSources >] {
                             param,
                             $(
@@ -684,26 +3405,25 @@ macro_rules! binding_n {
                             )*
                             dispatch,
                         };
-                        let node: BindingNode<T> = BindingNode {
-                            sources: sources.into(),
-                            target: None,
-                            holder: None,
-                        };
-                        (node.into(), id)
+                        let node: BindingNode<T> = BindingNode::new(sources.into());
+                        let mut node: AnyBindingNode = node.into();
+                        node.location = location;
+                        (node, id)
                     });
+                    bindings.register_in_scope(id);
                     [< BindingExt $n >] (id, PhantomType::new())
                 }
 
                 fn param_ref(state_part: &dyn Any, id: RawId) -> &P {
                     let bindings = state_part.downcast_ref::<Bindings>().unwrap();
-                    let node = bindings.0[Id::from_raw(id)].downcast_ref::<T>();
+                    let node = bindings.nodes[Id::from_raw(id)].downcast_ref::<T>();
                     let sources = node.sources.downcast_ref::< [< BindingExt $n NodeSources >] <P, $( [< S $i >] ,)* T>>();
                     &sources.param
                 }
 
                 fn param_mut(state_part: &mut dyn Any, id: RawId) -> &mut P {
                     let bindings = state_part.downcast_mut::<Bindings>().unwrap();
-                    let node = bindings.0[Id::from_raw(id)].downcast_mut::<T>();
+                    let node = bindings.nodes[Id::from_raw(id)].downcast_mut::<T>();
                     let sources = node.sources.downcast_mut::< [< BindingExt $n NodeSources >] <P, $( [< S $i >] ,)* T>>();
                     &mut sources.param
                 }
@@ -723,6 +3443,22 @@ macro_rules! binding_n {
                     BindingBase::from(self).set_holder(state, holder);
                 }
 
+                pub fn set_schedule(self, state: &mut dyn State, schedule: BindingSchedule) {
+                    BindingBase::from(self).set_schedule(state, schedule);
+                }
+
+                pub fn is_enabled(self, state: &dyn State) -> bool {
+                    BindingBase::from(self).is_enabled(state)
+                }
+
+                pub fn set_enabled(self, state: &mut dyn State, enabled: bool) {
+                    BindingBase::from(self).set_enabled(state, enabled);
+                }
+
+                pub fn resume(self, state: &mut dyn State) {
+                    BindingBase::from(self).resume(state);
+                }
+
                 pub fn dispatch<Context: Clone + 'static>(
                     self,
                     state: &mut dyn State,
@@ -741,10 +3477,39 @@ macro_rules! binding_n {
                     BindingBase::from(self).set_target_fn(state, context, execute);
                 }
 
+                pub fn dispatch_closure(
+                    self,
+                    state: &mut dyn State,
+                    execute: impl Fn(&mut dyn State, T) -> Re<!> + Clone + 'static
+                ) {
+                    BindingBase::from(self).dispatch_closure(state, execute);
+                }
+
+                pub fn set_target_closure(
+                    self,
+                    state: &mut dyn State,
+                    execute: impl Fn(&mut dyn State, T) + Clone + 'static
+                ) {
+                    BindingBase::from(self).set_target_closure(state, execute);
+                }
+
                 pub fn drop_self(self, state: &mut dyn State) {
                     AnyBindingBase::from(self).drop_self(state);
                 }
 
+                pub fn add_target(
+                    self,
+                    state: &mut dyn State,
+                    target: Box<dyn Target<T>>,
+                    holder: Option<Box<dyn Holder>>
+                ) -> BindingTargetId<T> {
+                    BindingBase::from(self).add_target(state, target, holder)
+                }
+
+                pub fn source(self) -> BindingSource<T, NoCache> {
+                    BindingBase::from(self).source()
+                }
+
                 $(
                     pub fn [< set_source_ $i >] (self, state: &mut dyn State, source: &mut [< S $i >] ) {
                         let handler: [< BindingExt $n Source $i Handler >] ::<P, $( [< S $j >] ,)* T>  = [< BindingExt $n Source $i Handler >] {
@@ -755,17 +3520,98 @@ macro_rules! binding_n {
                             state,
                             Box::new(handler)
                         );
+                        #[cfg(debug_assertions)]
+                        check_cycle(state, self.0, &*source.handler_id);
                         let bindings: &mut Bindings = state.get_mut();
-                        let node = bindings.0[self.0].downcast_mut::<T>();
+                        let node = bindings.nodes[self.0].downcast_mut::<T>();
                         let sources = node.sources.downcast_mut::< [< BindingExt $n NodeSources >] <P, $( [< S $j >] ,)* T>>();
                         if sources. [< source_ $i >] .replace((source.handler_id, [< S $i >] ::Cache::default() )).is_some() {
                             panic!("duplicate source");
                         }
+                        bindings.link(self.0);
                         source.init.map(|x| x(state));
                     }
                 )*
             }
 
+            impl<
+                F: Fn(
+                    &mut dyn State,
+                    $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),*
+                ) -> Re<T> + 'static,
+                $( [< S $i >] : Source + 'static, )*
+                T: Convenient
+            > [< BindingExt $n >] <Closure<Rc<F>>, $( [< S $i >] , )* T> {
+                /// Creates a binding with the closure `dispatch` function.
+                ///
+                /// The closure is shared, because it gets the state containing the binding,
+                /// so an evaluation does not clone it.
+                #[track_caller]
+                pub fn new_closure(state: &mut dyn State, dispatch: F) -> Self {
+                    let binding = Self::new(state, Closure::empty(), |state, f, $( [< value_ $i >] ),*| {
+                        let f = f.get(state).get().clone();
+                        f(state, $( [< value_ $i >] ),*)
+                    });
+                    let bindings: &mut Bindings = state.get_mut();
+                    let node = bindings.nodes[binding.0].downcast_mut::<T>();
+                    node.sources.place_closure(
+                        |sources: &mut [< BindingExt $n NodeSources >] <Closure<Rc<F>>, $( [< S $i >] ,)* T>| &mut sources.param,
+                        Rc::new(dispatch)
+                    );
+                    binding
+                }
+            }
+
+            impl<
+                P: Clone + Debug + 'static,
+                E: Convenient,
+                $( [< S $i >] : Source + 'static, )*
+                T: Convenient
+            > [< BindingExt $n >] <
+                Fallible<P, E, fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>>,
+                $( [< S $i >] , )*
+                T
+            > {
+                /// Creates a binding with the fallible `filter_map` function.
+                ///
+                /// If the function fails, the binding target is not called,
+                /// and the error is sent to the error target (see
+                /// [`set_error_target`](Self::set_error_target)).
+                /// The first successful evaluation after a failure sends `None` to the error target,
+                /// so a bound validation error can be cleared.
+                #[track_caller]
+                pub fn new_fallible(
+                    state: &mut dyn State,
+                    param: P,
+                    filter_map: fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>,
+                ) -> Self {
+                    let param = Fallible::new(param, filter_map);
+                    Self::new(state, param, |state, param, $( [< value_ $i >] ),*| {
+                        Fallible::evaluate(state, param, |param, filter_map| filter_map(param, $( [< value_ $i >] ),*))
+                    })
+                }
+
+                pub fn set_error_target(self, state: &mut dyn State, target: Box<dyn Target<Option<E>>>) {
+                    let bindings: &mut Bindings = state.get_mut();
+                    let node = bindings.nodes[self.0].downcast_mut::<T>();
+                    let sources = node.sources.downcast_mut::< [< BindingExt $n NodeSources >] <
+                        Fallible<P, E, fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>>,
+                        $( [< S $i >] , )*
+                        T
+                    >>();
+                    sources.param.error_target = Some(target);
+                }
+
+                pub fn set_error_target_fn<Context: Clone + 'static>(
+                    self,
+                    state: &mut dyn State,
+                    context: Context,
+                    execute: fn(state: &mut dyn State, context: Context, error: Option<E>)
+                ) {
+                    self.set_error_target(state, Box::new(FnTarget { context, execute }));
+                }
+            }
+
             impl<
                 P,
                 $( [< S $i >] : Source, )*
@@ -805,7 +3651,7 @@ macro_rules! binding_n {
                 > AnyHandler for [< BindingExt $n Source $i Handler >] <P, $( [< S $j >] , )* T >  {
                     fn clear(&self, state: &mut dyn State) {
                         let bindings: &mut Bindings = state.get_mut();
-                        let node = bindings.0[self.binding].downcast_mut::<T>();
+                        let node = bindings.nodes[self.binding].downcast_mut::<T>();
                         let sources = node.sources.downcast_mut::< [< BindingExt $n NodeSources >] <P, $( [< S $j >] ,)* T>>();
                         sources. [< source_ $i >] .take();
                     }
@@ -822,9 +3668,16 @@ macro_rules! binding_n {
 
                     fn execute(&self, state: &mut dyn State, value: [< S $i >] ::Value ) {
                         let bindings: &mut Bindings = state.get_mut();
-                        let node = bindings.0[self.binding].downcast_mut::<T>();
+                        let enabled = bindings.nodes[self.binding].enabled;
+                        let node = bindings.nodes[self.binding].downcast_mut::<T>();
+                        let schedule = node.schedule;
                         let sources = node.sources.downcast_mut::< [< BindingExt $n NodeSources >] <P, $( [< S $j >] ,)* T>>();
                         sources. [< source_ $i >] .as_mut().unwrap().1.update(value.clone());
+                        if !enabled { return; }
+                        if schedule != BindingSchedule::Immediate {
+                            enqueue(state, self.binding, schedule);
+                            return;
+                        }
                         $(
                             #[allow(unused_assignments, unused_mut)]
                             let mut [< current_ $j >] = None;
@@ -843,13 +3696,14 @@ macro_rules! binding_n {
                             }
                         )*
 
-                        let target = node.target.clone();
+                        let dispatch = sources.dispatch;
+                        let outputs = node.outputs(self.binding);
                         let param = Param {
                             id: self.binding.into_raw(),
                             descriptor: & < [< BindingExt $n >] <P, $( [< S $j >] ,)* T> > ::PARAM_DESCRIPTOR
                         };
-                        if let Re(Some(value)) = (sources.dispatch)(state, param, $( [< value_ $j >] ),*) {
-                            target.map(|x| x.execute(state, value));
+                        if let Re(Some(value)) = dispatch(state, param, $( [< value_ $j >] ),*) {
+                            outputs.execute(state, value);
                         }
                     }
                 }
@@ -863,7 +3717,12 @@ macro_rules! binding_n {
                     [< source_ $i >] : Option<(Box<dyn HandlerId>, [< S $i >] ::Cache )>,
                 )*
                 #[educe(Debug(ignore))]
-                filter_map: fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Option<T>,
+                filter_map: FilterMap<
+                    fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Option<T>,
+                    fn(&P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Option<T>
+                >,
+                #[educe(Debug(ignore))]
+                hooks: Option<EvaluationHooks<P>>,
             }
 
             impl<
@@ -886,15 +3745,35 @@ macro_rules! binding_n {
             > [< Binding $n NodeSources >] <P, $( [< S $i >] , )* T> {
                 const VTABLE: AnyBindingNodeSourcesVtable<T> = AnyBindingNodeSourcesVtable {
                     ty: TypeId::of::<Self>(),
+                    kind: concat!("Binding", $n),
+                    describe: Self::describe,
                     drop: Self::drop,
                     is_empty: Self::is_empty,
                     unhandle: Self::unhandle,
                     get_value: Self::get_value,
+                    evaluate: Self::evaluate,
                 };
 
+                fn evaluate(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+                    let bindings: &Bindings = state.get();
+                    let node = bindings.nodes[binding].downcast_ref::<T>();
+                    let sources = node.sources.downcast_ref::<Self>();
+                    let hooks = sources.hooks;
+                    if let Some(hooks) = hooks {
+                        (hooks.begin)(&sources.param);
+                    }
+                    let value = unsafe { Self::get_value(&node.sources.buf) };
+                    let outputs = value.is_some().then(|| node.outputs(binding));
+                    if let Some(hooks) = hooks {
+                        (hooks.end)(state, binding);
+                    }
+                    if let (Some(value), Some(outputs)) = (value, outputs) {
+                        outputs.execute(state, value);
+                    }
+                }
+
                 unsafe fn drop(buf: &mut BindingNodeSourcesBuf) {
-                    let this: *mut Self = buf.as_mut_ptr();
-                    ptr::drop_in_place(this);
+                    buf.drop_in_place::<Self>();
                 }
 
                 #[allow(unused_variables)]
@@ -908,6 +3787,18 @@ macro_rules! binding_n {
                     true
                 }
 
+                #[allow(unused_variables, unused_mut)]
+                unsafe fn describe(buf: &BindingNodeSourcesBuf) -> Vec<SourceDescriptor> {
+                    let this: &Self = &*buf.as_ptr();
+                    let mut sources = Vec::new();
+                    $(
+                        if let Some(source) = this. [< source_ $i >] .as_ref() {
+                            sources.push(source.0.describe());
+                        }
+                    )*
+                    sources
+                }
+
                 #[allow(unused_variables)]
                 unsafe fn unhandle(buf: &mut BindingNodeSourcesBuf, state: &mut dyn State, dropping_binding: AnyBindingBase) {
                     let this: &mut Self = &mut *buf.as_mut_ptr();
@@ -932,7 +3823,18 @@ macro_rules! binding_n {
                             return None;
                         }
                     )*
-                    (this.filter_map)(this.param.clone(), $( [< value_ $i >] ),*)
+                    this.filter_map($( [< value_ $i >] ),*)
+                }
+
+                #[allow(clippy::too_many_arguments)]
+                fn filter_map(
+                    &self,
+                    $( [< value_ $i >] : < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),*
+                ) -> Option<T> {
+                    match self.filter_map {
+                        FilterMap::Owned(filter_map) => filter_map(self.param.clone(), $( [< value_ $i >] ),*),
+                        FilterMap::Ref(filter_map) => filter_map(&self.param, $( [< value_ $i >] ),*),
+                    }
                 }
             }
 
@@ -950,27 +3852,42 @@ macro_rules! binding_n {
                 $( [< S $i >] : Source + 'static, )*
                 T: Convenient
             > [< Binding $n >] <P, $( [< S $i >] , )* T> {
+                #[track_caller]
                 pub fn new(
                     state: &mut dyn State,
                     param: P,
                     filter_map: fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Option<T>,
                 ) -> Self {
+                    Self::new_raw(state, param, FilterMap::Owned(filter_map), None)
+                }
+
+                #[track_caller]
+                fn new_raw(
+                    state: &mut dyn State,
+                    param: P,
+                    filter_map: FilterMap<
+                        fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Option<T>,
+                        fn(&P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Option<T>
+                    >,
+                    hooks: Option<EvaluationHooks<P>>,
+                ) -> Self {
+                    let location = caller_location();
                     let bindings: &mut Bindings = state.get_mut();
-                    let id = bindings.0.insert(|id| {
+                    let id = bindings.nodes.insert(|id| {
                         let sources: [< Binding $n NodeSources >] <P, $( [< S $i >] ,)* T> = [< Binding $n NodeSources >] {
                             param,
                             $(
                                 [< source_ $i >] : None,
                             )*
                             filter_map,
+                            hooks,
                         };
-                        let node: BindingNode<T> = BindingNode {
-                            sources: sources.into(),
-                            target: None,
-                            holder: None,
-                        };
-                        (node.into(), id)
+                        let node: BindingNode<T> = BindingNode::new(sources.into());
+                        let mut node: AnyBindingNode = node.into();
+                        node.location = location;
+                        (node, id)
                     });
+                    bindings.register_in_scope(id);
                     [< Binding $n >] (id, PhantomType::new())
                 }
 
@@ -982,6 +3899,22 @@ macro_rules! binding_n {
                     BindingBase::from(self).set_holder(state, holder);
                 }
 
+                pub fn set_schedule(self, state: &mut dyn State, schedule: BindingSchedule) {
+                    BindingBase::from(self).set_schedule(state, schedule);
+                }
+
+                pub fn is_enabled(self, state: &dyn State) -> bool {
+                    BindingBase::from(self).is_enabled(state)
+                }
+
+                pub fn set_enabled(self, state: &mut dyn State, enabled: bool) {
+                    BindingBase::from(self).set_enabled(state, enabled);
+                }
+
+                pub fn resume(self, state: &mut dyn State) {
+                    BindingBase::from(self).resume(state);
+                }
+
                 pub fn dispatch<Context: Clone + 'static>(
                     self,
                     state: &mut dyn State,
@@ -1000,6 +3933,22 @@ macro_rules! binding_n {
                     BindingBase::from(self).set_target_fn(state, context, execute);
                 }
 
+                pub fn dispatch_closure(
+                    self,
+                    state: &mut dyn State,
+                    execute: impl Fn(&mut dyn State, T) -> Re<!> + Clone + 'static
+                ) {
+                    BindingBase::from(self).dispatch_closure(state, execute);
+                }
+
+                pub fn set_target_closure(
+                    self,
+                    state: &mut dyn State,
+                    execute: impl Fn(&mut dyn State, T) + Clone + 'static
+                ) {
+                    BindingBase::from(self).set_target_closure(state, execute);
+                }
+
                 pub fn drop_self(self, state: &mut dyn State) {
                     AnyBindingBase::from(self).drop_self(state);
                 }
@@ -1008,6 +3957,19 @@ macro_rules! binding_n {
                     Binding::from(self).get_value(state)
                 }
 
+                pub fn add_target(
+                    self,
+                    state: &mut dyn State,
+                    target: Box<dyn Target<T>>,
+                    holder: Option<Box<dyn Holder>>
+                ) -> BindingTargetId<T> {
+                    Binding::from(self).add_target(state, target, holder)
+                }
+
+                pub fn source(self) -> BindingSource<T> {
+                    Binding::from(self).source()
+                }
+
                 $(
                     pub fn [< set_source_ $i >] (self, state: &mut dyn State, source: &mut [< S $i >] ) {
                         let handler: [< Binding $n Source $i Handler >] ::<P, $( [< S $j >] ,)* T>  = [< Binding $n Source $i Handler >] {
@@ -1018,17 +3980,113 @@ macro_rules! binding_n {
                             state,
                             Box::new(handler)
                         );
+                        #[cfg(debug_assertions)]
+                        check_cycle(state, self.0, &*source.handler_id);
                         let bindings: &mut Bindings = state.get_mut();
-                        let node = bindings.0[self.0].downcast_mut::<T>();
+                        let node = bindings.nodes[self.0].downcast_mut::<T>();
                         let sources = node.sources.downcast_mut::< [< Binding $n NodeSources >] <P, $( [< S $j >] ,)* T>>();
                         if sources. [< source_ $i >] .replace((source.handler_id, [< S $i >] ::Cache::default() )).is_some() {
                             panic!("duplicate source");
                         }
+                        bindings.link(self.0);
                         source.init.map(|x| x(state));
                     }
                 )*
             }
 
+            impl<
+                F: Fn(
+                    $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),*
+                ) -> Option<T> + Clone + 'static,
+                $( [< S $i >] : Source + 'static, )*
+                T: Convenient
+            > [< Binding $n >] <Closure<F>, $( [< S $i >] , )* T> {
+                /// Creates a binding with the closure `filter_map` function.
+                #[track_caller]
+                pub fn new_closure(state: &mut dyn State, filter_map: F) -> Self {
+                    let binding = Self::new_raw(
+                        state,
+                        Closure::empty(),
+                        FilterMap::Ref(|f, $( [< value_ $i >] ),*| (f.get())($( [< value_ $i >] ),*)),
+                        None
+                    );
+                    let bindings: &mut Bindings = state.get_mut();
+                    let node = bindings.nodes[binding.0].downcast_mut::<T>();
+                    node.sources.place_closure(
+                        |sources: &mut [< Binding $n NodeSources >] <Closure<F>, $( [< S $i >] ,)* T>| &mut sources.param,
+                        filter_map
+                    );
+                    binding
+                }
+            }
+
+            impl<
+                P: Clone + 'static,
+                E: Convenient,
+                $( [< S $i >] : Source + 'static, )*
+                T: Convenient
+            > [< Binding $n >] <
+                Fallible<P, E, fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>>,
+                $( [< S $i >] , )*
+                T
+            > {
+                /// Creates a binding with the fallible `filter_map` function.
+                ///
+                /// If the function fails, the binding target is not called,
+                /// and the error is sent to the error target (see
+                /// [`set_error_target`](Self::set_error_target)).
+                /// The first successful evaluation after a failure sends `None` to the error target,
+                /// so a bound validation error can be cleared.
+                #[track_caller]
+                pub fn new_fallible(
+                    state: &mut dyn State,
+                    param: P,
+                    filter_map: fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>,
+                ) -> Self {
+                    Self::new_raw(
+                        state,
+                        Fallible::new(param, filter_map),
+                        FilterMap::Ref(|this, $( [< value_ $i >] ),*| {
+                            this.filter_map_ref(|param, filter_map| filter_map(param, $( [< value_ $i >] ),*))
+                        }),
+                        Some(EvaluationHooks { begin: Fallible::begin, end: Self::report_error })
+                    )
+                }
+
+                fn report_error(state: &mut dyn State, binding: Id<AnyBindingNode>) {
+                    let bindings: &mut Bindings = state.get_mut();
+                    let node = bindings.nodes[binding].downcast_mut::<T>();
+                    let sources = node.sources.downcast_mut::< [< Binding $n NodeSources >] <
+                        Fallible<P, E, fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>>,
+                        $( [< S $i >] , )*
+                        T
+                    >>();
+                    if let Some((error_target, error)) = sources.param.report() {
+                        error_target.execute(state, error);
+                    }
+                }
+
+                pub fn set_error_target(self, state: &mut dyn State, target: Box<dyn Target<Option<E>>>) {
+                    let bindings: &mut Bindings = state.get_mut();
+                    let node = bindings.nodes[self.0].downcast_mut::<T>();
+                    let sources = node.sources.downcast_mut::< [< Binding $n NodeSources >] <
+                        Fallible<P, E, fn(P, $( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value ),* ) -> Result<T, E>>,
+                        $( [< S $i >] , )*
+                        T
+                    >>();
+                    sources.param.error_target = Some(target);
+                }
+
+                pub fn set_error_target_fn<Context: Clone + 'static>(
+                    self,
+                    state: &mut dyn State,
+                    context: Context,
+                    execute: fn(state: &mut dyn State, context: Context, error: Option<E>)
+                ) {
+                    self.set_error_target(state, Box::new(FnTarget { context, execute }));
+                }
+            }
+
             impl<
                 P,
                 $( [< S $i >] : Source, )*
@@ -1078,7 +4136,7 @@ macro_rules! binding_n {
                 > AnyHandler for [< Binding $n Source $i Handler >] <P, $( [< S $j >] , )* T >  {
                     fn clear(&self, state: &mut dyn State) {
                         let bindings: &mut Bindings = state.get_mut();
-                        let node = bindings.0[self.binding].downcast_mut::<T>();
+                        let node = bindings.nodes[self.binding].downcast_mut::<T>();
                         let sources = node.sources.downcast_mut::< [< Binding $n NodeSources >] <P, $( [< S $j >] ,)* T>>();
                         sources. [< source_ $i >] .take();
                     }
@@ -1095,9 +4153,20 @@ macro_rules! binding_n {
 
                     fn execute(&self, state: &mut dyn State, value: [< S $i >] ::Value ) {
                         let bindings: &mut Bindings = state.get_mut();
-                        let node = bindings.0[self.binding].downcast_mut::<T>();
+                        let enabled = bindings.nodes[self.binding].enabled;
+                        let node = bindings.nodes[self.binding].downcast_mut::<T>();
+                        let schedule = node.schedule;
                         let sources = node.sources.downcast_mut::< [< Binding $n NodeSources >] <P, $( [< S $j >] ,)* T>>();
                         sources. [< source_ $i >] .as_mut().unwrap().1.update(value.clone());
+                        if !enabled { return; }
+                        if schedule != BindingSchedule::Immediate {
+                            enqueue(state, self.binding, schedule);
+                            return;
+                        }
+                        let hooks = sources.hooks;
+                        if let Some(hooks) = hooks {
+                            (hooks.begin)(&sources.param);
+                        }
                         $(
                             #[allow(unused_assignments, unused_mut)]
                             let mut [< current_ $j >] = None;
@@ -1116,10 +4185,13 @@ macro_rules! binding_n {
                             }
                         )*
 
-                        if let Some(value) = (sources.filter_map)(sources.param.clone(), $( [< value_ $j >] ),*) {
-                            if let Some(target) = node.target.clone() {
-                                target.execute(state, value);
-                            }
+                        let value = sources.filter_map($( [< value_ $j >] ),*);
+                        let outputs = value.is_some().then(|| node.outputs(self.binding));
+                        if let Some(hooks) = hooks {
+                            (hooks.end)(state, self.binding);
+                        }
+                        if let (Some(value), Some(outputs)) = (value, outputs) {
+                            outputs.execute(state, value);
                         }
                     }
                 }
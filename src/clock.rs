@@ -0,0 +1,86 @@
+//! A manually driven clock for time-based binding operators.
+//!
+//! The [`Clock`] state part does not read any real time source.
+//! Instead, the application advances it with the [`Clock::tick`] method,
+//! which makes time-based behavior deterministic and testable.
+//! Time is measured in application-defined units (e.g. milliseconds).
+
+use crate::binding::Bindings;
+use crate::recorder::Recorder;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use dyn_context::{SelfState, State, StateExt};
+use educe::Educe;
+
+/// A scheduled timer id.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct TimerId {
+    at: u64,
+    seq: u64,
+}
+
+impl TimerId {
+    /// Returns the time, when the timer fires.
+    pub fn at(self) -> u64 { self.at }
+}
+
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct Clock {
+    now: u64,
+    seq: u64,
+    #[educe(Debug(ignore))]
+    timers: BTreeMap<TimerId, Box<dyn FnOnce(&mut dyn State, TimerId)>>,
+}
+
+impl SelfState for Clock { }
+
+impl const Default for Clock {
+    fn default() -> Self { Clock::new() }
+}
+
+impl Clock {
+    pub const fn new() -> Self { Clock { now: 0, seq: 0, timers: BTreeMap::new() } }
+
+    pub fn now(&self) -> u64 { self.now }
+
+    /// Schedules the `callback` call after `delay` time units.
+    ///
+    /// Timers scheduled at the same time fire in the scheduling order.
+    pub fn schedule(&mut self, delay: u64, callback: Box<dyn FnOnce(&mut dyn State, TimerId)>) -> TimerId {
+        let timer = TimerId { at: self.now.saturating_add(delay), seq: self.seq };
+        self.seq += 1;
+        self.timers.insert(timer, callback);
+        timer
+    }
+
+    /// Cancels the timer. Returns `false` if the timer is already fired or cancelled.
+    pub fn cancel(&mut self, timer: TimerId) -> bool {
+        self.timers.remove(&timer).is_some()
+    }
+
+    /// Advances the clock to `now`, firing all timers scheduled at or before it.
+    ///
+    /// While a timer callback runs, the [`now`](Clock::now) method returns the timer time,
+    /// so timers scheduled from callbacks are placed correctly.
+    ///
+    /// Panics if `now` is less than the current clock time.
+    pub fn tick(state: &mut dyn State, now: u64) {
+        Recorder::run_driven(state, |state| {
+            let clock: &mut Clock = state.get_mut();
+            assert!(now >= clock.now, "clock cannot go backwards");
+            Bindings::run_propagation(state, |state| loop {
+                let clock: &mut Clock = state.get_mut();
+                let timer = match clock.timers.keys().next().copied() {
+                    Some(timer) if timer.at <= now => timer,
+                    _ => break,
+                };
+                let callback = clock.timers.remove(&timer).unwrap();
+                clock.now = timer.at;
+                callback(state, timer);
+            });
+            let clock: &mut Clock = state.get_mut();
+            clock.now = now;
+        })
+    }
+}
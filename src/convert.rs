@@ -0,0 +1,305 @@
+//! Reusable value conversions for bindings.
+//!
+//! A [`ValueConverter`] can be used instead of a dispatch function in
+//! [`Binding1::new_converted`], [`BindingExt1::new_converted`], ...,
+//! [`DepProp::bind_converted`](crate::DepProp::bind_converted),
+//! and [`DepProp::link_two_way`](crate::DepProp::link_two_way).
+//!
+//! Bindings with several sources pass source values to the converter as a tuple.
+
+use crate::Convenient;
+use crate::binding::{Re, Source, SourceCache};
+use crate::binding::n::*;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use core::cell::Cell;
+use core::fmt::{Debug, Display};
+use core::str::FromStr;
+use dyn_context::State;
+use educe::Educe;
+use paste::paste;
+use phantom_type::PhantomType;
+
+/// A conversion between `A` and `B` values.
+///
+/// Both methods return `None` if a value cannot be converted,
+/// in which case a binding does not update its target.
+pub trait ValueConverter<A, B> {
+    fn convert(&self, value: A) -> Option<B>;
+
+    /// Converts a value in the opposite direction, used by two-way links.
+    ///
+    /// The default implementation converts nothing.
+    fn convert_back(&self, _value: B) -> Option<A> { None }
+}
+
+impl<
+    C: ValueConverter<<S::Cache as SourceCache<S::Value>>::Value, T> + Clone + 'static,
+    S: Source + 'static,
+    T: Convenient
+> Binding1<C, S, T> {
+    /// Creates a binding converting the source value with the `converter`.
+    #[track_caller]
+    pub fn new_converted(state: &mut dyn State, converter: C) -> Self {
+        Self::new(state, converter, |converter, value| converter.convert(value))
+    }
+}
+
+impl<
+    C: ValueConverter<<S::Cache as SourceCache<S::Value>>::Value, T> + Debug + 'static,
+    S: Source + 'static,
+    T: Convenient
+> BindingExt1<C, S, T> {
+    /// Creates a binding converting the source value with the `converter`.
+    #[track_caller]
+    pub fn new_converted(state: &mut dyn State, converter: C) -> Self {
+        Self::new(state, converter, |state, converter, value| {
+            if let Some(value) = converter.get(state).convert(value) { Re::Yield(value) } else { Re::Continue }
+        })
+    }
+}
+
+macro_rules! new_converted_n {
+    ($n:tt; $($i:tt),+) => {
+        paste! {
+            impl<
+                C: ValueConverter<
+                    ($( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value, )+),
+                    T
+                > + Clone + 'static,
+                $( [< S $i >] : Source + 'static, )+
+                T: Convenient
+            > [< Binding $n >] <C, $( [< S $i >] , )+ T> {
+                /// Creates a binding converting the tuple of source values with the `converter`.
+                #[track_caller]
+                pub fn new_converted(state: &mut dyn State, converter: C) -> Self {
+                    Self::new(state, converter, |converter, $( [< value_ $i >] ),+| {
+                        converter.convert(($( [< value_ $i >] , )+))
+                    })
+                }
+            }
+
+            impl<
+                C: ValueConverter<
+                    ($( < < [< S $i >] as Source > ::Cache as SourceCache< [< S $i >] ::Value > >::Value, )+),
+                    T
+                > + Debug + 'static,
+                $( [< S $i >] : Source + 'static, )+
+                T: Convenient
+            > [< BindingExt $n >] <C, $( [< S $i >] , )+ T> {
+                /// Creates a binding converting the tuple of source values with the `converter`.
+                #[track_caller]
+                pub fn new_converted(state: &mut dyn State, converter: C) -> Self {
+                    Self::new(state, converter, |state, converter, $( [< value_ $i >] ),+| {
+                        let value = converter.get(state).convert(($( [< value_ $i >] , )+));
+                        if let Some(value) = value { Re::Yield(value) } else { Re::Continue }
+                    })
+                }
+            }
+        }
+    };
+}
+
+new_converted_n!(2; 1, 2);
+new_converted_n!(3; 1, 2, 3);
+new_converted_n!(4; 1, 2, 3, 4);
+new_converted_n!(5; 1, 2, 3, 4, 5);
+new_converted_n!(6; 1, 2, 3, 4, 5, 6);
+new_converted_n!(7; 1, 2, 3, 4, 5, 6, 7);
+new_converted_n!(8; 1, 2, 3, 4, 5, 6, 7, 8);
+new_converted_n!(9; 1, 2, 3, 4, 5, 6, 7, 8, 9);
+new_converted_n!(10; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10);
+new_converted_n!(11; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11);
+new_converted_n!(12; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12);
+new_converted_n!(13; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
+new_converted_n!(14; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14);
+new_converted_n!(15; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+new_converted_n!(16; 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+
+/// One direction of a [`link_two_way`](crate::DepProp::link_two_way) link.
+///
+/// Drops the value sent by the opposite direction when it comes back,
+/// so lossy conversions do not rewrite the value it originated from.
+#[derive(Educe)]
+#[educe(Debug(bound="C: Debug"), Clone(bound="C: Clone"))]
+pub(crate) struct LinkDirection<A, B, C> {
+    converter: C,
+    #[educe(Debug(ignore))]
+    sent: Rc<Cell<Option<B>>>,
+    #[educe(Debug(ignore))]
+    received: Rc<Cell<Option<A>>>,
+}
+
+impl<A, B, C> LinkDirection<A, B, C> {
+    /// Creates both link directions.
+    pub(crate) fn new<D>(converter: C, back_converter: D) -> (Self, LinkDirection<B, A, D>) {
+        let there = Rc::new(Cell::new(None));
+        let back = Rc::new(Cell::new(None));
+        (
+            LinkDirection { converter, sent: there.clone(), received: back.clone() },
+            LinkDirection { converter: back_converter, sent: back, received: there }
+        )
+    }
+}
+
+impl<A: PartialEq, B: Clone, C: ValueConverter<A, B>> ValueConverter<A, B> for LinkDirection<A, B, C> {
+    fn convert(&self, value: A) -> Option<B> {
+        if self.received.take().map_or(false, |received| received == value) { return None; }
+        let value = self.converter.convert(value);
+        self.sent.set(value.clone());
+        value
+    }
+}
+
+/// Swaps the wrapped converter directions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Inverse<C>(pub C);
+
+impl<A, B, C: ValueConverter<A, B>> ValueConverter<B, A> for Inverse<C> {
+    fn convert(&self, value: B) -> Option<A> { self.0.convert_back(value) }
+
+    fn convert_back(&self, value: A) -> Option<B> { self.0.convert(value) }
+}
+
+/// A primitive numeric type cast, used by [`Cast`].
+pub trait NumCast<T>: Sized {
+    /// Returns `None` if the value is out of the `T` range.
+    ///
+    /// Casts from floating point types to integer ones truncate the fractional part,
+    /// and fail for NaN and infinite values.
+    /// Casts to floating point types round the value to the nearest representable one,
+    /// and fail only if the result is infinite while the source value is finite.
+    fn num_cast(self) -> Option<T>;
+}
+
+macro_rules! int_num_cast {
+    ($($from:ty),+) => {
+        $(
+            int_num_cast!(@to $from; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+            impl NumCast<f32> for $from {
+                fn num_cast(self) -> Option<f32> { Some(self as f32).filter(|x| x.is_finite()) }
+            }
+
+            impl NumCast<f64> for $from {
+                fn num_cast(self) -> Option<f64> { Some(self as f64) }
+            }
+        )+
+    };
+    (@to $from:ty; $($to:ty),+) => {
+        $(
+            impl NumCast<$to> for $from {
+                fn num_cast(self) -> Option<$to> { <$to>::try_from(self).ok() }
+            }
+        )+
+    };
+}
+
+int_num_cast!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float_num_cast {
+    ($($from:ty),+) => {
+        $(
+            float_num_cast!(@to $from; i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+            impl NumCast<f32> for $from {
+                fn num_cast(self) -> Option<f32> {
+                    let value = self as f32;
+                    if value.is_infinite() && self.is_finite() { None } else { Some(value) }
+                }
+            }
+
+            impl NumCast<f64> for $from {
+                fn num_cast(self) -> Option<f64> { Some(self as f64) }
+            }
+        )+
+    };
+    (@to $from:ty; $($to:ty),+) => {
+        $(
+            impl NumCast<$to> for $from {
+                fn num_cast(self) -> Option<$to> {
+                    if self.is_nan() { return None; }
+                    if self < 0.0 {
+                        if self < i128::MIN as $from { return None; }
+                        <$to>::try_from(self as i128).ok()
+                    } else {
+                        if self >= u128::MAX as $from { return None; }
+                        <$to>::try_from(self as u128).ok()
+                    }
+                }
+            }
+        )+
+    };
+}
+
+float_num_cast!(f32, f64);
+
+/// A primitive numeric type cast, failing if the value does not fit the target type
+/// (see [`NumCast`]).
+#[derive(Educe)]
+#[educe(Debug, Clone, Copy, Default)]
+pub struct Cast<A, B>(PhantomType<(A, B)>);
+
+impl<A, B> Cast<A, B> {
+    pub const fn new() -> Self { Cast(PhantomType::new()) }
+}
+
+impl<A: NumCast<B>, B: NumCast<A>> ValueConverter<A, B> for Cast<A, B> {
+    fn convert(&self, value: A) -> Option<B> { value.num_cast() }
+
+    fn convert_back(&self, value: B) -> Option<A> { value.num_cast() }
+}
+
+/// Boolean negation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Not;
+
+impl ValueConverter<bool, bool> for Not {
+    fn convert(&self, value: bool) -> Option<bool> { Some(!value) }
+
+    fn convert_back(&self, value: bool) -> Option<bool> { Some(!value) }
+}
+
+/// Replaces `None` with the wrapped default value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnwrapOr<T>(pub T);
+
+impl<T: Clone> ValueConverter<Option<T>, T> for UnwrapOr<T> {
+    fn convert(&self, value: Option<T>) -> Option<T> {
+        Some(value.unwrap_or_else(|| self.0.clone()))
+    }
+
+    fn convert_back(&self, value: T) -> Option<Option<T>> { Some(Some(value)) }
+}
+
+/// Formats a value with its [`Display`] implementation.
+///
+/// The conversion is one-way, use [`Parse`] (or [`Inverse<Parse>`](Inverse))
+/// for types implementing [`FromStr`] as well.
+#[derive(Educe)]
+#[educe(Debug, Clone, Copy, Default)]
+pub struct Format<A>(PhantomType<A>);
+
+impl<A> Format<A> {
+    pub const fn new() -> Self { Format(PhantomType::new()) }
+}
+
+impl<A: Display> ValueConverter<A, String> for Format<A> {
+    fn convert(&self, value: A) -> Option<String> { Some(value.to_string()) }
+}
+
+/// Parses a value with its [`FromStr`] implementation, and formats it back
+/// with its [`Display`] implementation.
+#[derive(Educe)]
+#[educe(Debug, Clone, Copy, Default)]
+pub struct Parse<A>(PhantomType<A>);
+
+impl<A> Parse<A> {
+    pub const fn new() -> Self { Parse(PhantomType::new()) }
+}
+
+impl<A: FromStr + Display> ValueConverter<String, A> for Parse<A> {
+    fn convert(&self, value: String) -> Option<A> { value.parse().ok() }
+
+    fn convert_back(&self, value: A) -> Option<String> { Some(value.to_string()) }
+}
@@ -1,4 +1,5 @@
 #![feature(allocator_api)]
+#![feature(const_btree_new)]
 #![feature(const_maybe_uninit_as_mut_ptr)]
 #![feature(const_mut_refs)]
 #![feature(const_ptr_offset_from)]
@@ -31,6 +32,14 @@ pub use base::*;
 
 pub mod binding;
 
+pub mod clock;
+
+pub mod convert;
+
+pub mod recorder;
+
+pub mod style_text;
+
 #[cfg(docsrs)]
 pub mod example {
     //! The [`dep_type`], [`impl_dep_obj`], and [`with_builder`]
@@ -217,8 +226,12 @@ pub use memoffset::offset_of as memoffset_offset_of;
 pub use paste::paste as paste_paste;
 
 use crate::binding::*;
+use crate::convert::{Inverse, LinkDirection, ValueConverter};
+use crate::recorder::Recorder;
 use alloc::boxed::Box;
-use alloc::collections::VecDeque;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use arrayvec::ArrayVec;
 use components_arena::{Arena, ArenaItemsIntoValues, Component, ComponentId, Id, RawId};
@@ -226,12 +239,16 @@ use composable_allocators::Global;
 use composable_allocators::fallbacked::Fallbacked;
 use composable_allocators::stacked::{self};
 use core::alloc::Allocator;
-use core::any::{Any, TypeId};
-use core::fmt::Debug;
+use core::any::{Any, TypeId, type_name};
+use core::cell::Cell;
+use core::cmp::Reverse;
+use core::fmt::{self, Debug, Display, Formatter};
 use core::iter::once;
-use core::mem::{replace, take};
-use core::ops::{Deref, DerefMut};
-use dyn_context::State;
+use core::marker::PhantomData;
+use core::mem::{replace, take, transmute};
+use core::ops::{Bound::{Excluded, Unbounded}, Deref, DerefMut};
+use core::str::FromStr;
+use dyn_context::{SelfState, State};
 use educe::Educe;
 use macro_attr_2018::macro_attr;
 use phantom_type::PhantomType;
@@ -251,6 +268,16 @@ macro_attr! {
     struct BoxedHandler<T>(Box<dyn Handler<T>>);
 }
 
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct EventHandler<ArgsType> {
+    handler: Box<dyn Handler<ArgsType>>,
+    handled_too: bool,
+}
+
+/// An event handler key, ordering handlers by descending priority, and then by registration order.
+type EventHandlerKey = (Reverse<i32>, u64);
+
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
 pub struct Change<PropType: Convenient> {
     pub old: PropType,
@@ -400,6 +427,11 @@ impl<PropType: Convenient> DepPropHandlersCopy<PropType> {
         id: Owner::Id,
         prop: DepProp<Owner, PropType>
     ) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        if let Some(class_handlers) = ClassHandlers::prop_handlers(state, prop) {
+            for class_handler in class_handlers.iter() {
+                class_handler(state, id.into_raw(), change);
+            }
+        }
         if let Some(change_initial_handler) = self.change_initial_handler {
             change_initial_handler.execute(state, change.clone());
         }
@@ -522,6 +554,7 @@ pub struct DepPropEntry<PropType: Convenient> {
     local: Option<PropType>,
     handlers: DepPropHandlers<PropType>,
     binding: Option<BindingBase<PropType>>,
+    target_binding: Option<BindingTargetId<PropType>>,
     queue: OneStack<VecDeque<Option<PropType>>>,
     enqueue: bool,
 }
@@ -534,6 +567,7 @@ impl<PropType: Convenient> DepPropEntry<PropType> {
             style: None,
             local: None,
             binding: None,
+            target_binding: None,
             queue: OneStack::new(),
             enqueue: false,
         }
@@ -544,6 +578,9 @@ impl<PropType: Convenient> DepPropEntry<PropType> {
     #[doc(hidden)]
     pub fn take_all_handlers<A: Allocator>(&mut self, handlers: &mut Vec<Box<dyn AnyHandler>, A>) {
         self.handlers.take_all(handlers);
+        if let Some(target_binding) = self.target_binding.take() {
+            handlers.push(Box::new(target_binding));
+        }
     }
 
     #[doc(hidden)]
@@ -552,23 +589,57 @@ impl<PropType: Convenient> DepPropEntry<PropType> {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum DepEventRoute {
+    Direct,
+    Bubble,
+    Tunnel,
+}
+
 #[derive(Debug)]
 pub struct DepEventEntry<ArgsType: DepEventArgs> {
-    bubble: bool,
-    handlers: Arena<BoxedHandler<ArgsType>>,
+    route: DepEventRoute,
+    cached: bool,
+    last: Option<ArgsType>,
+    handlers: BTreeMap<EventHandlerKey, EventHandler<ArgsType>>,
+    seq: u64,
 }
 
 impl<ArgsType: DepEventArgs> DepEventEntry<ArgsType> {
-    pub const fn new(bubble: bool) -> Self {
+    const fn new_raw(route: DepEventRoute, cached: bool) -> Self {
         DepEventEntry {
-            bubble,
-            handlers: Arena::new(),
+            route,
+            cached,
+            last: None,
+            handlers: BTreeMap::new(),
+            seq: 0,
         }
     }
 
+    pub const fn new(bubble: bool) -> Self {
+        Self::new_raw(if bubble { DepEventRoute::Bubble } else { DepEventRoute::Direct }, false)
+    }
+
+    pub const fn new_tunnel() -> Self {
+        Self::new_raw(DepEventRoute::Tunnel, false)
+    }
+
+    /// Creates a `#[cached]` event entry.
+    ///
+    /// The entry remembers the last args raised on its object (but not args routed through it
+    /// from other objects), and a handler attached later receives them,
+    /// unless they were handled and the handler is not a [`source_handled_too`](DepEvent::source_handled_too) one.
+    pub const fn new_cached(bubble: bool) -> Self {
+        Self::new_raw(if bubble { DepEventRoute::Bubble } else { DepEventRoute::Direct }, true)
+    }
+
+    pub const fn new_tunnel_cached() -> Self {
+        Self::new_raw(DepEventRoute::Tunnel, true)
+    }
+
     #[doc(hidden)]
     pub fn take_all_handlers<A: Allocator>(&mut self, handlers: &mut Vec<Box<dyn AnyHandler>, A>) {
-        handlers.extend(take(&mut self.handlers).into_items().into_values().map(|x| x.0.into_any()));
+        handlers.extend(take(&mut self.handlers).into_values().map(|x| x.handler.into_any()));
     }
 }
 
@@ -693,6 +764,7 @@ impl<ItemType: Convenient> DepVecEntry<ItemType> {
 #[derive(Debug)]
 pub struct BaseDepObjCore<Owner: DepType + 'static> {
     style: Option<Style<Owner>>,
+    visual_states: Vec<VisualState<Owner>>,
     added_bindings: Arena<AnyBindingBase>,
 }
 
@@ -700,6 +772,7 @@ impl<Owner: DepType> BaseDepObjCore<Owner> {
     pub const fn new() -> Self {
         BaseDepObjCore {
             style: None,
+            visual_states: Vec::new(),
             added_bindings: Arena::new(),
         }
     }
@@ -734,38 +807,104 @@ pub trait DepObjId: ComponentId {
         state: &mut dyn State,
         style: Option<Style<Owner>>,
     ) -> Option<Style<Owner>> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
-        stacked::with_size::<256, _>(|alloc| {
-            let mut on_changed = Vec::new_in(Fallbacked(alloc, Global));
-            let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, self.into_raw());
-            let old = obj.core_base_priv_mut().style.take();
-            if let Some(old) = old.as_ref() {
-                old.setters
-                    .iter()
-                    .filter(|setter| style.as_ref().map_or(
-                        true,
-                        |new| new.setters.binary_search_by_key(
-                            &setter.prop_offset(),
-                            |x| x.prop_offset()
-                        ).is_err()
-                    ))
-                    .filter_map(|setter| setter.un_apply(state, self, true))
-                    .for_each(|x| on_changed.push(x))
-                ;
-            }
-            if let Some(new) = style.as_ref() {
-                new.setters
-                    .iter()
-                    .filter_map(|setter| setter.un_apply(state, self, false))
-                    .for_each(|x| on_changed.push(x))
-                ;
+        restyle(state, self, Some(style), None)
+    }
+}
+
+/// Replaces the object style and/or one of its visual states,
+/// applying only setters which effective values differ.
+///
+/// Visual state setters override the object style ones. Returns the replaced object style.
+fn restyle<Owner: DepType>(
+    state: &mut dyn State,
+    id: Owner::Id,
+    style: Option<Option<Style<Owner>>>,
+    visual_state: Option<VisualState<Owner>>,
+) -> Option<Style<Owner>> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+    stacked::with_size::<256, _>(|alloc| {
+        let alloc = Fallbacked(alloc, Global);
+        let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+        let core = obj.core_base_priv_mut();
+        let mut current = core.style.take();
+        let mut visual_states = take(&mut core.visual_states);
+        let replaced = style.map(|style| replace(&mut current, style));
+        let (index, replaced_state) = if let Some(visual_state) = visual_state {
+            if let Some(index) = visual_states.iter().position(|x| x.group == visual_state.group) {
+                (index, Some(replace(&mut visual_states[index], visual_state)))
+            } else {
+                visual_states.push(visual_state);
+                (visual_states.len() - 1, None)
             }
-            let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, self.into_raw());
-            obj.core_base_priv_mut().style = style;
+        } else {
+            (usize::MAX, None)
+        };
+        let mut on_changed = Vec::new_in(&alloc);
+        {
+            let old_style = replaced.as_ref().map_or(current.as_ref(), |x| x.as_ref());
+            let old_states = visual_states.iter().enumerate()
+                .filter_map(|(i, x)| if i == index { replaced_state.as_ref() } else { Some(x) });
+            let mut old_setters = Vec::new_in(&alloc);
+            effective_setters(old_style.into_iter().chain(old_states.flat_map(VisualState::layers)), &mut old_setters);
+            let mut new_setters = Vec::new_in(&alloc);
+            effective_setters(
+                current.iter().chain(visual_states.iter().flat_map(VisualState::layers)),
+                &mut new_setters
+            );
+            old_setters
+                .iter()
+                .filter(|setter| new_setters.binary_search_by_key(&setter.prop_offset(), |x| x.prop_offset()).is_err())
+                .filter_map(|setter| setter.un_apply(state, id, true))
+                .for_each(|x| on_changed.push(x))
+            ;
+            new_setters
+                .iter()
+                .filter_map(|setter| setter.un_apply(state, id, false))
+                .for_each(|x| on_changed.push(x))
+            ;
+        }
+        let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+        let core = obj.core_base_priv_mut();
+        core.style = current;
+        core.visual_states = visual_states;
+        Bindings::run_propagation(state, |state| {
             for on_changed in on_changed {
                 on_changed(state);
             }
-            old
-        })
+        });
+        replaced.flatten()
+    })
+}
+
+/// Calls `leave` when dropped, even if unwinding.
+///
+/// The guarded code accesses the state through a reference derived from the `state` pointer.
+/// It is sound as long as the original reference is not used after the guard creation,
+/// because the guard dereferences the pointer only in `drop`, after the derived reference is gone.
+struct LeaveGuard<F: FnMut(&mut dyn State)> {
+    state: *mut dyn State,
+    leave: F,
+}
+
+impl<F: FnMut(&mut dyn State)> Drop for LeaveGuard<F> {
+    fn drop(&mut self) {
+        // SAFETY: see the type documentation.
+        (self.leave)(unsafe { &mut *self.state });
+    }
+}
+
+/// Collects setters from `layers`, sorted by property offset. Later layers override earlier ones.
+fn effective_setters<'a, Owner: DepType + 'static, A: Allocator>(
+    layers: impl Iterator<Item=&'a Style<Owner>>,
+    setters: &mut Vec<&'a dyn AnySetter<Owner>, A>
+) {
+    for layer in layers {
+        for setter in layer.setters.iter() {
+            let setter: &dyn AnySetter<Owner> = setter.as_ref();
+            match setters.binary_search_by_key(&setter.prop_offset(), |x| x.prop_offset()) {
+                Ok(index) => setters[index] = setter,
+                Err(index) => setters.insert(index, setter),
+            }
+        }
     }
 }
 
@@ -884,6 +1023,81 @@ pub trait DepType: Debug {
 
 pub trait DepEventArgs: Convenient {
     fn handled(&self) -> bool;
+
+    /// Returns a copy, which handled state is not shared with `self` anymore,
+    /// but starts with the `self` state.
+    ///
+    /// Every raising detaches the passed args, so handlers on the route share one handled state
+    /// with each other, but not with the caller, the cached args, or recorded args.
+    ///
+    /// The default implementation clones `self`, which is enough for args without shared state.
+    fn detach_handled(&self) -> Self { self.clone() }
+}
+
+/// Event arguments with a handled flag.
+///
+/// Every handler on the event route gets a clone sharing the same flag,
+/// so [`set_handled`](Handled::set_handled) called in a handler is visible
+/// to the rest of the route, stopping it. Each raising gets its own flag
+/// (see [`DepEventArgs::detach_handled`]), so the args passed to
+/// [`raise`](DepEvent::raise) can be raised again. The flag is not compared by `PartialEq`.
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+pub struct Handled<T: Convenient> {
+    args: T,
+    handled: Rc<Cell<bool>>,
+}
+
+impl<T: Convenient> Handled<T> {
+    pub fn new(args: T) -> Self { Handled { args, handled: Rc::new(Cell::new(false)) } }
+
+    pub fn args(&self) -> &T { &self.args }
+
+    pub fn set_handled(&self) { self.handled.set(true); }
+}
+
+impl<T: Convenient> PartialEq for Handled<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.args == other.args
+    }
+}
+
+impl<T: Convenient> Deref for Handled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { &self.args }
+}
+
+/// Writes the args, followed by ` (handled)` if the flag is set.
+impl<T: Convenient + Display> Display for Handled<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.args)?;
+        if self.handled() { write!(f, " (handled)")?; }
+        Ok(())
+    }
+}
+
+/// Parses the [`Display`] output.
+impl<T: Convenient + FromStr> FromStr for Handled<T> {
+    type Err = T::Err;
+
+    fn from_str(s: &str) -> Result<Self, T::Err> {
+        if let Some(args) = s.strip_suffix(" (handled)") {
+            let handled = Handled::<T>::new(args.parse()?);
+            handled.set_handled();
+            Ok(handled)
+        } else {
+            Ok(Handled::new(s.parse()?))
+        }
+    }
+}
+
+impl<T: Convenient> DepEventArgs for Handled<T> {
+    fn handled(&self) -> bool { self.handled.get() }
+
+    fn detach_handled(&self) -> Self {
+        Handled { args: self.args.clone(), handled: Rc::new(Cell::new(self.handled.get())) }
+    }
 }
 
 #[derive(Educe)]
@@ -920,37 +1134,217 @@ impl<Owner: DepType, ArgsType: DepEventArgs> DepEvent<Owner, ArgsType> {
         }
     }
 
+    fn route(self, state: &dyn State, id: Owner::Id) -> DepEventRoute where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        let obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get(state, id.into_raw());
+        self.entry(&obj).route
+    }
+
     fn raise_raw(
         self, state: &mut dyn State, id: Owner::Id, args: &ArgsType
-    ) -> bool where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
-        let obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get(state, id.into_raw());
-        let entry = self.entry(&obj);
-        let bubble = entry.bubble;
-        let handlers = entry.handlers.items().clone().into_values();
-        for handler in handlers {
-            handler.0.execute(state, args.clone());
+    ) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        if let Some(class_handlers) = ClassHandlers::event_handlers(state, self) {
+            for class_handler in class_handlers.iter() {
+                if args.handled() { break; }
+                class_handler(state, id.into_raw(), args);
+            }
+        }
+        let mut last = None;
+        loop {
+            let obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get(state, id.into_raw());
+            let entry = self.entry(&obj);
+            let next = if let Some(last) = last {
+                entry.handlers.range((Excluded(last), Unbounded)).next()
+            } else {
+                entry.handlers.iter().next()
+            };
+            let (&key, handler) = if let Some(next) = next { next } else { break; };
+            last = Some(key);
+            if args.handled() && !handler.handled_too { continue; }
+            let handler = handler.handler.clone();
+            handler.execute(state, args.clone());
         }
-        bubble
     }
 
+    /// Raises the event.
+    ///
+    /// A `#[bubble]` event is raised on the source object first, and then on its ancestors.
+    /// A `#[tunnel]` event goes in the opposite direction: from the root object down to the source.
+    ///
+    /// Handlers attached to the same object are executed in descending priority order
+    /// (see [`DepEventSource::with_priority`]).
+    /// As soon as `args.handled()` returns `true`, only handlers registered with
+    /// [`source_handled_too`](DepEvent::source_handled_too) on the current object are executed,
+    /// and the event is not routed further.
     pub fn raise<X: Convenient>(
-        self, state: &mut dyn State, mut id: Owner::Id, args: ArgsType
+        self, state: &mut dyn State, id: Owner::Id, args: ArgsType
     ) -> Re<X> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
-        let bubble = self.raise_raw(state, id, &args);
-        if !bubble || args.handled() { return Re::Continue; }
-        while let Some(parent) = id.parent(state) {
-            id = parent;
-            let bubble = self.raise_raw(state, id, &args);
-            debug_assert!(bubble);
-            if args.handled() { return Re::Continue; }
-        }
+        self.raise_detached(state, id, args.detach_handled());
+        Re::Continue
+    }
+
+    fn raise_detached(
+        self, state: &mut dyn State, id: Owner::Id, args: ArgsType
+    ) -> ArgsType where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        Recorder::run_event(state, id, self, args, |state, args| Bindings::run_propagation(state, |state| {
+            let source = id;
+            let mut id = id;
+            match self.route(state, id) {
+                DepEventRoute::Direct => self.raise_raw(state, id, &args),
+                DepEventRoute::Bubble => {
+                    self.raise_raw(state, id, &args);
+                    while !args.handled() {
+                        if let Some(parent) = id.parent(state) { id = parent; } else { break; }
+                        self.raise_raw(state, id, &args);
+                    }
+                },
+                DepEventRoute::Tunnel => stacked::with_size::<256, _>(|alloc| {
+                    let mut route = Vec::new_in(Fallbacked(alloc, Global));
+                    route.push(id);
+                    while let Some(parent) = id.parent(state) {
+                        id = parent;
+                        route.push(id);
+                    }
+                    for id in route.into_iter().rev() {
+                        self.raise_raw(state, id, &args);
+                        if args.handled() { break; }
+                    }
+                }),
+            }
+            let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, source.into_raw());
+            let entry_mut = self.entry_mut(&mut obj);
+            if entry_mut.cached {
+                entry_mut.last = Some(args.detach_handled());
+            }
+            args
+        }))
+    }
+
+    /// Raises the `preview` `#[tunnel]` event, and then raises this event with the same arguments.
+    ///
+    /// If the preview event was handled, this event reaches
+    /// [`source_handled_too`](DepEvent::source_handled_too) handlers only.
+    ///
+    /// Panics if the preview event is not `#[tunnel]`.
+    pub fn raise_with_preview<X: Convenient>(
+        self, state: &mut dyn State, id: Owner::Id, preview: DepEvent<Owner, ArgsType>, args: ArgsType
+    ) -> Re<X> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        assert!(preview.route(state, id) == DepEventRoute::Tunnel, "preview event should be '#[tunnel]'");
+        let args = preview.raise_detached(state, id, args.detach_handled());
+        self.raise_detached(state, id, args);
         Re::Continue
     }
 
     pub fn source(self, id: Owner::Id) -> DepEventSource<Owner, ArgsType> where
         Owner::Id: DepObj<Owner::DepObjKey, Owner> {
 
-        DepEventSource { id, event: self }
+        DepEventSource { id, event: self, priority: 0, handled_too: false }
+    }
+
+    /// Returns a source, which handlers are executed even for already handled events.
+    pub fn source_handled_too(self, id: Owner::Id) -> DepEventSource<Owner, ArgsType> where
+        Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+
+        DepEventSource { id, event: self, priority: 0, handled_too: true }
+    }
+}
+
+/// Class-level property and event handlers.
+///
+/// A class handler is registered once per property or event,
+/// and is executed for every instance of the owning dependency type
+/// before instance handlers.
+///
+/// Class handlers are optional: they are executed only if the state contains a `ClassHandlers` part.
+/// Handlers are identified by the owner type id and the member offset.
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct ClassHandlers {
+    #[educe(Debug(ignore))]
+    handlers: BTreeMap<(TypeId, usize), Box<dyn Any>>,
+    count: usize,
+}
+
+/// Returns the `Owner` type id, ignoring lifetimes, so `DepType` does not need to be `'static`.
+pub(crate) fn owner_type_id<Owner: ?Sized>() -> TypeId {
+    trait NonStaticAny {
+        fn get_type_id(&self) -> TypeId where Self: 'static;
+    }
+
+    impl<T: ?Sized> NonStaticAny for PhantomData<T> {
+        fn get_type_id(&self) -> TypeId where Self: 'static { TypeId::of::<T>() }
+    }
+
+    let phantom = PhantomData::<Owner>;
+    // SAFETY: lifetimes do not affect the type id, and the erased reference is not stored.
+    let phantom = unsafe { transmute::<&dyn NonStaticAny, &(dyn NonStaticAny + 'static)>(&phantom) };
+    phantom.get_type_id()
+}
+
+type ClassPropHandler<PropType> = dyn Fn(&mut dyn State, RawId, &Change<PropType>);
+
+type ClassEventHandler<ArgsType> = dyn Fn(&mut dyn State, RawId, &ArgsType);
+
+impl SelfState for ClassHandlers { }
+
+impl const Default for ClassHandlers {
+    fn default() -> Self { ClassHandlers::new() }
+}
+
+impl ClassHandlers {
+    pub const fn new() -> Self { ClassHandlers { handlers: BTreeMap::new(), count: 0 } }
+
+    fn add<H: ?Sized + 'static>(&mut self, key: (TypeId, usize), handler: Rc<H>) {
+        let handlers = self.handlers.entry(key)
+            .or_insert_with(|| Box::new(Rc::new(Vec::<Rc<H>>::new())) as Box<dyn Any>)
+            .downcast_mut::<Rc<Vec<Rc<H>>>>()
+            .expect("class handlers type mismatch");
+        Rc::make_mut(handlers).push(handler);
+        self.count += 1;
+    }
+
+    fn get<H: ?Sized + 'static>(state: &dyn State, key: (TypeId, usize)) -> Option<Rc<Vec<Rc<H>>>> {
+        let class_handlers = state.get_raw(TypeId::of::<ClassHandlers>())?;
+        let class_handlers = class_handlers.downcast_ref::<ClassHandlers>().unwrap();
+        if class_handlers.count == 0 { return None; }
+        let handlers = class_handlers.handlers.get(&key)?;
+        Some(handlers.downcast_ref::<Rc<Vec<Rc<H>>>>().unwrap().clone())
+    }
+
+    /// Registers a handler executed on every property value change in every `Owner` instance.
+    pub fn add_prop_handler<Owner: DepType, PropType: Convenient>(
+        &mut self,
+        prop: DepProp<Owner, PropType>,
+        handler: fn(state: &mut dyn State, id: Owner::Id, change: &Change<PropType>)
+    ) where Owner::Id: 'static {
+        let handler: Rc<ClassPropHandler<PropType>> = Rc::new(move |state, id, change| handler(state, Owner::Id::from_raw(id), change));
+        self.add((owner_type_id::<Owner>(), prop.offset), handler);
+    }
+
+    /// Registers a handler executed on every event raising in every `Owner` instance
+    /// the event is routed through.
+    ///
+    /// Class handlers are not executed for already handled events.
+    pub fn add_event_handler<Owner: DepType, ArgsType: DepEventArgs>(
+        &mut self,
+        event: DepEvent<Owner, ArgsType>,
+        handler: fn(state: &mut dyn State, id: Owner::Id, args: &ArgsType)
+    ) where Owner::Id: 'static {
+        let handler: Rc<ClassEventHandler<ArgsType>> = Rc::new(move |state, id, args| handler(state, Owner::Id::from_raw(id), args));
+        self.add((owner_type_id::<Owner>(), event.offset), handler);
+    }
+
+    fn prop_handlers<Owner: DepType, PropType: Convenient>(
+        state: &dyn State,
+        prop: DepProp<Owner, PropType>
+    ) -> Option<Rc<Vec<Rc<ClassPropHandler<PropType>>>>> {
+        Self::get(state, (owner_type_id::<Owner>(), prop.offset))
+    }
+
+    fn event_handlers<Owner: DepType, ArgsType: DepEventArgs>(
+        state: &dyn State,
+        event: DepEvent<Owner, ArgsType>
+    ) -> Option<Rc<Vec<Rc<ClassEventHandler<ArgsType>>>>> {
+        Self::get(state, (owner_type_id::<Owner>(), event.offset))
     }
 }
 
@@ -1117,30 +1511,49 @@ impl<Owner: DepType, PropType: Convenient> DepProp<Owner, PropType> {
         let entry_mut = self.entry_mut(&mut obj);
         if replace(&mut entry_mut.enqueue, true) {
             entry_mut.queue.push_back(value);
+            #[cfg(debug_assertions)]
+            Bindings::prop_queued(state, type_name::<Owner>(), self.offset, id.into_raw());
             return;
         }
+        #[cfg(debug_assertions)]
+        Bindings::enter_prop(state, type_name::<Owner>(), self.offset, id.into_raw());
+        // the queue is dropped if a handler panics, so the property stays usable
+        let guard = LeaveGuard { state, leave: move |state: &mut dyn State| {
+            let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+            let entry_mut = self.entry_mut(&mut obj);
+            entry_mut.enqueue = false;
+            entry_mut.queue = OneStack::new();
+            #[cfg(debug_assertions)]
+            Bindings::leave_prop(state);
+        } };
+        // SAFETY: see `LeaveGuard`.
+        let state = unsafe { &mut *guard.state };
+        let mut iterations = 0;
         loop {
             self.un_set_core(state, id, value);
             let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
             let entry_mut = self.entry_mut(&mut obj);
             if let Some(queue_head) = entry_mut.queue.pop_front() { value = queue_head; } else { break; }
+            iterations += 1;
+            Bindings::check_iterations(state, iterations, type_name::<Owner>(), self.offset, id.into_raw());
         }
-        let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
-        let entry_mut = self.entry_mut(&mut obj);
-        entry_mut.enqueue = false;
     }
 
     pub fn set<X: Convenient>(
         self, state: &mut dyn State, id: Owner::Id, value: PropType
     ) -> Re<X> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
-        self.un_set(state, id, Some(value));
+        Recorder::run_prop(state, id, self, Some(value), |state, value| {
+            Bindings::run_propagation(state, |state| self.un_set(state, id, value));
+        });
         Re::Continue
     }
 
     pub fn unset<X: Convenient>(
         self, state: &mut dyn State, id: Owner::Id
     ) -> Re<X> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
-        self.un_set(state, id, None);
+        Recorder::run_prop(state, id, self, None, |state, value| {
+            Bindings::run_propagation(state, |state| self.un_set(state, id, value));
+        });
         Re::Continue
     }
 
@@ -1167,16 +1580,88 @@ impl<Owner: DepType, PropType: Convenient> DepProp<Owner, PropType> {
         self.bind_raw(state, id, binding.into());
     }
 
+    /// Binds the property to the `source` value converted with the `converter`
+    /// (see [`Binding1::new_converted`]).
+    pub fn bind_converted<S: Source + 'static, C>(
+        self,
+        state: &mut dyn State,
+        id: Owner::Id,
+        mut source: S,
+        converter: C
+    ) where
+        C: ValueConverter<<S::Cache as SourceCache<S::Value>>::Value, PropType> + Clone + 'static,
+        Owner: 'static,
+        Owner::Id: DepObj<Owner::DepObjKey, Owner>
+    {
+        let binding = Binding1::new_converted(state, converter);
+        self.bind(state, id, binding);
+        binding.set_source_1(state, &mut source);
+    }
+
+    /// Keeps the property and the `other` property in sync, converting values
+    /// with the `converter` [`convert`](ValueConverter::convert) method in one direction,
+    /// and with the [`convert_back`](ValueConverter::convert_back) method in the opposite one.
+    ///
+    /// Both properties get bound (replacing existing bindings), and the `other` property
+    /// is initialized from this one.
+    ///
+    /// A value converted from one property is not converted back when the other property changes to it,
+    /// so lossy conversions (e.g. [`UnwrapOr`](convert::UnwrapOr), or [`Parse`](convert::Parse)
+    /// normalizing "05" to "5") do not rewrite the property the value came from.
+    pub fn link_two_way<Other: DepType + 'static, OtherType: Convenient, C>(
+        self,
+        state: &mut dyn State,
+        id: Owner::Id,
+        other: DepProp<Other, OtherType>,
+        other_id: Other::Id,
+        converter: C
+    ) where
+        C: ValueConverter<PropType, OtherType> + Clone + 'static,
+        Owner: 'static,
+        Owner::Id: DepObj<Owner::DepObjKey, Owner>,
+        Other::Id: DepObj<Other::DepObjKey, Other>
+    {
+        let (there, back) = LinkDirection::new(converter.clone(), Inverse(converter));
+        other.bind_converted(state, other_id, self.value_source(id), there);
+        self.bind_converted(state, id, other.value_source(other_id), back);
+    }
+
+    /// Makes the property one of the shared binding additional targets
+    /// (see [`BindingBase::add_target`]).
+    ///
+    /// Unlike [`bind`](DepProp::bind), the property does not own the binding:
+    /// the link is removed when either the object or the binding is dropped.
+    pub fn bind_target(
+        self,
+        state: &mut dyn State,
+        id: Owner::Id,
+        binding: impl Into<BindingBase<PropType>>
+    ) where Owner: 'static, Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        self.unbind(state, id);
+        let target_binding = binding.into().add_target(
+            state,
+            Box::new(DepPropSet { prop: self, id }),
+            Some(Box::new(DepPropTargetHolder { prop: self, id }))
+        );
+        let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+        let entry_mut = self.entry_mut(&mut obj);
+        entry_mut.target_binding = Some(target_binding);
+    }
+
     pub fn unbind(
         self, state: &mut dyn State, id: Owner::Id
     ) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
-        if let Some(binding) = {
+        let (binding, target_binding) = {
             let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
             let entry_mut = self.entry_mut(&mut obj);
-            entry_mut.binding
-        } {
+            (entry_mut.binding, entry_mut.target_binding)
+        };
+        if let Some(binding) = binding {
             binding.drop_self(state);
         }
+        if let Some(target_binding) = target_binding {
+            target_binding.remove(state);
+        }
     }
 
     fn clear_binding(self, state: &mut dyn State, id: Owner::Id) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
@@ -1186,10 +1671,45 @@ impl<Owner: DepType, PropType: Convenient> DepProp<Owner, PropType> {
         debug_assert!(ok);
     }
 
+    fn clear_target_binding(self, state: &mut dyn State, id: Owner::Id) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+        let entry_mut = self.entry_mut(&mut obj);
+        let ok = entry_mut.target_binding.take().is_some();
+        debug_assert!(ok);
+    }
+
+    /// Returns a binding target setting the property value,
+    /// e.g. a fallible binding error target (see [`BindingExt1::set_error_target`]).
+    ///
+    /// Unlike [`bind`](DepProp::bind), the target does not own a binding,
+    /// and it is not released when the property is unbound.
+    pub fn target(
+        self,
+        id: Owner::Id
+    ) -> Box<dyn Target<PropType>> where Owner: 'static, Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        Box::new(DepPropSet { prop: self, id })
+    }
+
     pub fn value_source(self, id: Owner::Id) -> DepPropValueSource<Owner, PropType> {
         DepPropValueSource { id, prop: self }
     }
 
+    /// Returns a source following the object referenced by the property value,
+    /// e.g. `NpcProps::TARGET.path_source(npc, |x| x.map(|x| NpcProps::WEAPON.value_source(x)))`.
+    ///
+    /// When the property changes, the source selected by `f` for the old value is unsubscribed,
+    /// and the source selected for the new value is subscribed. If `f` returns `None`,
+    /// the default value is sent. Longer paths can be built with [`Source::switch`].
+    pub fn path_source<I: Source + 'static>(
+        self,
+        id: Owner::Id,
+        f: fn(PropType) -> Option<I>
+    ) -> Switch<DepPropValueSource<Owner, PropType>, I> where
+        Owner: 'static, Owner::Id: DepObj<Owner::DepObjKey, Owner>, I::Value: Default {
+
+        self.value_source(id).switch(f)
+    }
+
     pub fn change_source(self, id: Owner::Id) -> DepPropChangeSource<Owner, PropType> {
         DepPropChangeSource { id, prop: self }
     }
@@ -1203,6 +1723,10 @@ impl<Owner: DepType, PropType: Convenient> DepProp<Owner, PropType> {
     }
 }
 
+fn describe_member<Owner: DepType>(kind: MemberKind, id: Owner::Id, offset: usize) -> SourceDescriptor {
+    SourceDescriptor::Member { kind, owner: type_name::<Owner>(), offset, id: id.into_raw() }
+}
+
 #[derive(Educe)]
 #[educe(Debug, Clone)]
 struct DepPropSet<Owner: DepType, PropType: Convenient> where
@@ -1212,12 +1736,14 @@ struct DepPropSet<Owner: DepType, PropType: Convenient> where
     prop: DepProp<Owner, PropType>,
 }
 
-impl<Owner: DepType, PropType: Convenient> Target<PropType> for DepPropSet<Owner, PropType> where
+impl<Owner: DepType + 'static, PropType: Convenient> Target<PropType> for DepPropSet<Owner, PropType> where
     Owner::Id: DepObj<Owner::DepObjKey, Owner> {
 
     fn execute(&self, state: &mut dyn State, value: PropType) {
         self.prop.set(state, self.id, value).immediate();
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()) }
 }
 
 impl<Owner: DepType, PropType: Convenient> Holder for DepPropSet<Owner, PropType> where
@@ -1226,6 +1752,23 @@ impl<Owner: DepType, PropType: Convenient> Holder for DepPropSet<Owner, PropType
     fn release(&self, state: &mut dyn State) {
         self.prop.clear_binding(state, self.id);
     }
+
+    fn describe(&self) -> String { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()).to_string() }
+}
+
+struct DepPropTargetHolder<Owner: DepType, PropType: Convenient> {
+    id: Owner::Id,
+    prop: DepProp<Owner, PropType>,
+}
+
+impl<Owner: DepType, PropType: Convenient> Holder for DepPropTargetHolder<Owner, PropType> where
+    Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+
+    fn release(&self, state: &mut dyn State) {
+        self.prop.clear_target_binding(state, self.id);
+    }
+
+    fn describe(&self) -> String { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()).to_string() }
 }
 
 #[derive(Debug)]
@@ -1338,67 +1881,75 @@ impl<Owner: DepType, ItemType: Convenient> DepVec<Owner, ItemType> {
             entry_mut.queue.push_back(modification);
             return;
         }
-        loop {
-            let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
-            let entry_mut = self.entry_mut(&mut obj);
-            match modification {
-                DepVecModification::Clear => {
-                    let items = take(&mut entry_mut.items);
-                    let handlers = entry_mut.handlers.clone();
-                    handlers.execute_remove(state, &items);
-                },
-                DepVecModification::Insert(pos, item) => {
-                    let index = pos.find(&entry_mut.items);
-                    let prev = if index == 0 { None } else { Some(entry_mut.items[index - 1].clone()) };
-                    entry_mut.items.insert(index, item.clone());
-                    let handlers = entry_mut.handlers.clone();
-                    handlers.execute_insert(state, prev, &[item]);
-                },
-                DepVecModification::Remove(pos) => {
-                    let index = pos.find(&entry_mut.items);
-                    let item = entry_mut.items.remove(index);
-                    let handlers = entry_mut.handlers.clone();
-                    handlers.execute_remove(state, &[item]);
-                },
-                DepVecModification::Move(old_pos, new_pos) => {
-                    let old_index = old_pos.find(&entry_mut.items);
-                    let item = entry_mut.items.remove(old_index);
-                    let new_index = new_pos.find(&entry_mut.items);
-                    let prev = if new_index == 0 { None } else { Some(entry_mut.items[new_index - 1].clone()) };
-                    entry_mut.items.insert(new_index, item.clone());
-                    let handlers = entry_mut.handlers.clone();
-                    handlers.execute_move(state, prev, item);
-                },
-                DepVecModification::ExtendFrom(vec) => {
-                    let prev = entry_mut.items.last().cloned();
-                    entry_mut.items.extend_from_slice(&vec);
-                    let handlers = entry_mut.handlers.clone();
-                    handlers.execute_insert(state, prev, &vec);
-                },
-                DepVecModification::Update(handler_id) => {
-                    let items = entry_mut.items.clone();
-                    let handler = handler_id.map_or_else(
-                        || entry_mut.handlers.item_initial_final_handler.as_ref().unwrap().handler.clone(),
-                        |handler_id| entry_mut.handlers.item_handlers[handler_id].handler.clone()
-                    );
-                    for item in &items {
-                        handler.execute(state, ItemChange { action: ItemChangeAction::UpdateRemove, item: item.clone() });
-                    }
-                    for (item, prev) in items.iter().zip(once(None).chain(items.iter().map(Some))) {
-                        handler.execute(state, ItemChange {
-                            action: ItemChangeAction::UpdateInsert { prev: prev.cloned() },
-                            item: item.clone()
-                        });
-                    }
-                },
-            };
+        // the queue is dropped if a handler panics, so the vector stays usable
+        let guard = LeaveGuard { state, leave: move |state: &mut dyn State| {
             let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
             let entry_mut = self.entry_mut(&mut obj);
-            if let Some(queue_head) = entry_mut.queue.pop_front() { modification = queue_head; } else { break; }
-        }
-        let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
-        let entry_mut = self.entry_mut(&mut obj);
-        entry_mut.enqueue = false;
+            entry_mut.enqueue = false;
+            entry_mut.queue = OneStack::new();
+        } };
+        // SAFETY: see `LeaveGuard`.
+        let state = unsafe { &mut *guard.state };
+        Bindings::run_propagation(state, |state| {
+            loop {
+                let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+                let entry_mut = self.entry_mut(&mut obj);
+                match modification {
+                    DepVecModification::Clear => {
+                        let items = take(&mut entry_mut.items);
+                        let handlers = entry_mut.handlers.clone();
+                        handlers.execute_remove(state, &items);
+                    },
+                    DepVecModification::Insert(pos, item) => {
+                        let index = pos.find(&entry_mut.items);
+                        let prev = if index == 0 { None } else { Some(entry_mut.items[index - 1].clone()) };
+                        entry_mut.items.insert(index, item.clone());
+                        let handlers = entry_mut.handlers.clone();
+                        handlers.execute_insert(state, prev, &[item]);
+                    },
+                    DepVecModification::Remove(pos) => {
+                        let index = pos.find(&entry_mut.items);
+                        let item = entry_mut.items.remove(index);
+                        let handlers = entry_mut.handlers.clone();
+                        handlers.execute_remove(state, &[item]);
+                    },
+                    DepVecModification::Move(old_pos, new_pos) => {
+                        let old_index = old_pos.find(&entry_mut.items);
+                        let item = entry_mut.items.remove(old_index);
+                        let new_index = new_pos.find(&entry_mut.items);
+                        let prev = if new_index == 0 { None } else { Some(entry_mut.items[new_index - 1].clone()) };
+                        entry_mut.items.insert(new_index, item.clone());
+                        let handlers = entry_mut.handlers.clone();
+                        handlers.execute_move(state, prev, item);
+                    },
+                    DepVecModification::ExtendFrom(vec) => {
+                        let prev = entry_mut.items.last().cloned();
+                        entry_mut.items.extend_from_slice(&vec);
+                        let handlers = entry_mut.handlers.clone();
+                        handlers.execute_insert(state, prev, &vec);
+                    },
+                    DepVecModification::Update(handler_id) => {
+                        let items = entry_mut.items.clone();
+                        let handler = handler_id.map_or_else(
+                            || entry_mut.handlers.item_initial_final_handler.as_ref().unwrap().handler.clone(),
+                            |handler_id| entry_mut.handlers.item_handlers[handler_id].handler.clone()
+                        );
+                        for item in &items {
+                            handler.execute(state, ItemChange { action: ItemChangeAction::UpdateRemove, item: item.clone() });
+                        }
+                        for (item, prev) in items.iter().zip(once(None).chain(items.iter().map(Some))) {
+                            handler.execute(state, ItemChange {
+                                action: ItemChangeAction::UpdateInsert { prev: prev.cloned() },
+                                item: item.clone()
+                            });
+                        }
+                    },
+                };
+                let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, id.into_raw());
+                let entry_mut = self.entry_mut(&mut obj);
+                if let Some(queue_head) = entry_mut.queue.pop_front() { modification = queue_head; } else { break; }
+            }
+        });
     }
 
     pub fn clear<X: Convenient>(
@@ -1812,6 +2363,25 @@ impl<Owner: DepType> Style<Owner> {
 
     pub fn len(&self) -> usize { self.setters.len() }
 
+    /// Inserts all `other` style values, overwriting existing values for the same properties.
+    ///
+    /// Fails without changing the style if the resulting properties count exceeds the style capacity.
+    pub fn merge(&mut self, other: &Style<Owner>) -> Result<(), StyleCapacityExceeded> {
+        let added = other.setters.iter()
+            .filter(|setter| self.setters.binary_search_by_key(&setter.prop_offset(), |x| x.prop_offset()).is_err())
+            .count();
+        if self.setters.len() + added > self.capacity() {
+            return Err(StyleCapacityExceeded { capacity: self.capacity() });
+        }
+        for setter in other.setters.iter() {
+            match self.setters.binary_search_by_key(&setter.prop_offset(), |x| x.prop_offset()) {
+                Ok(index) => self.setters[index] = setter.clone(),
+                Err(index) => self.setters.insert(index, setter.clone()),
+            }
+        }
+        Ok(())
+    }
+
     pub fn remove<PropType: Convenient>(&mut self, prop: DepProp<Owner, PropType>) -> bool {
         match self.setters.binary_search_by_key(&prop.offset, |x| x.prop_offset()) {
             Ok(index) => { self.setters.remove(index); true }
@@ -1820,11 +2390,127 @@ impl<Owner: DepType> Style<Owner> {
     }
 }
 
-pub trait DepObjBuilder {
-    type Id: ComponentId;
+/// The [`Style::merge`] method error.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StyleCapacityExceeded {
+    pub capacity: usize,
+}
 
-    fn state(&self) -> &dyn State;
-    fn state_mut(&mut self) -> &mut dyn State;
+impl Display for StyleCapacityExceeded {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "too many properties, style capacity is {}", self.capacity)
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct VisualState<Owner: DepType + 'static> {
+    group: &'static str,
+    name: &'static str,
+    base: Option<Style<Owner>>,
+    delta: Style<Owner>,
+}
+
+impl<Owner: DepType> VisualState<Owner> {
+    fn layers(&self) -> impl Iterator<Item=&Style<Owner>> {
+        self.base.iter().chain(once(&self.delta))
+    }
+}
+
+/// A named set of visual states, each defined as a [`Style`] delta over an optional base style.
+///
+/// Switching between states with [`go_to_state`](StateGroup::go_to_state)
+/// layers the state setters over the object style (see [`DepObjId::apply_style`])
+/// and over states of other groups, so only properties with differing effective values get notified.
+///
+/// An object has a current state per group. Groups are identified by name.
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+pub struct StateGroup<Owner: DepType + 'static> {
+    name: &'static str,
+    base: Option<Style<Owner>>,
+    states: Vec<(&'static str, Style<Owner>)>,
+    #[educe(Debug(ignore))]
+    transition: Option<fn(state: &mut dyn State, id: Owner::Id, old: Option<&'static str>, new: &'static str)>,
+}
+
+impl<Owner: DepType + 'static> StateGroup<Owner> {
+    pub const fn new(name: &'static str) -> Self {
+        StateGroup { name, base: None, states: Vec::new(), transition: None }
+    }
+
+    pub fn name(&self) -> &'static str { self.name }
+
+    /// Sets the style shared by all states.
+    pub fn base(mut self, base: Style<Owner>) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Adds a named state.
+    ///
+    /// Panics if the state with the same name is already added.
+    pub fn state(mut self, name: &'static str, delta: Style<Owner>) -> Self {
+        assert!(self.states.iter().all(|x| x.0 != name), "duplicate state '{}'", name);
+        self.states.push((name, delta));
+        self
+    }
+
+    /// Sets a hook called after every state switch.
+    pub fn on_transition(
+        mut self,
+        transition: fn(state: &mut dyn State, id: Owner::Id, old: Option<&'static str>, new: &'static str)
+    ) -> Self {
+        self.transition = Some(transition);
+        self
+    }
+
+    pub fn contains_state(&self, name: &str) -> bool {
+        self.states.iter().any(|x| x.0 == name)
+    }
+
+    /// Returns the name of the group state the object was last switched to.
+    pub fn current_state(
+        &self,
+        state: &dyn State,
+        id: Owner::Id
+    ) -> Option<&'static str> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        let obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get(state, id.into_raw());
+        obj.core_base_priv().visual_states.iter().find(|x| x.group == self.name).map(|x| x.name)
+    }
+
+    /// Switches the object to the named state, replacing the previous group state setters
+    /// with the base style and the state delta ones.
+    ///
+    /// Returns `false` without doing anything if the object is already in the requested state.
+    ///
+    /// Panics if there is no state with the specified name.
+    pub fn go_to_state(
+        &self,
+        state: &mut dyn State,
+        id: Owner::Id,
+        name: &str
+    ) -> bool where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        let &(name, ref delta) = self.states.iter().find(|x| x.0 == name)
+            .unwrap_or_else(|| panic!("unknown state '{}'", name));
+        let old = self.current_state(state, id);
+        if old == Some(name) { return false; }
+        let visual_state = VisualState { group: self.name, name, base: self.base.clone(), delta: delta.clone() };
+        Bindings::run_propagation(state, |state| {
+            restyle(state, id, None, Some(visual_state));
+            if let Some(transition) = self.transition {
+                transition(state, id, old, name);
+            }
+        });
+        true
+    }
+}
+
+pub trait DepObjBuilder {
+    type Id: ComponentId;
+
+    fn state(&self) -> &dyn State;
+    fn state_mut(&mut self) -> &mut dyn State;
     fn id(&self) -> Self::Id;
 }
 
@@ -1832,7 +2518,7 @@ pub trait DepObjBuilder {
 #[educe(Debug)]
 struct DepEventHandledSource<Owner: DepType, ArgsType: DepEventArgs> {
     id: Owner::Id,
-    handler_id: Id<BoxedHandler<ArgsType>>,
+    handler_id: EventHandlerKey,
     event: DepEvent<Owner, ArgsType>,
 }
 
@@ -1842,8 +2528,10 @@ impl<Owner: DepType, ArgsType: DepEventArgs> HandlerId for DepEventHandledSource
     fn unhandle(&self, state: &mut dyn State, _dropping_binding: AnyBindingBase) {
         let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, self.id.into_raw());
         let entry_mut = self.event.entry_mut(&mut obj);
-        entry_mut.handlers.remove(self.handler_id);
+        entry_mut.handlers.remove(&self.handler_id);
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Event, self.id, self.event.offset()) }
 }
 
 #[derive(Educe)]
@@ -1851,6 +2539,17 @@ impl<Owner: DepType, ArgsType: DepEventArgs> HandlerId for DepEventHandledSource
 pub struct DepEventSource<Owner: DepType, ArgsType: DepEventArgs> {
     id: Owner::Id,
     event: DepEvent<Owner, ArgsType>,
+    priority: i32,
+    handled_too: bool,
+}
+
+impl<Owner: DepType, ArgsType: DepEventArgs> DepEventSource<Owner, ArgsType> {
+    /// Sets the handler priority. Handlers with greater priority are executed first.
+    /// The default priority is zero.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 impl<Owner: DepType + 'static, ArgsType: DepEventArgs + 'static> Source for DepEventSource<Owner, ArgsType> where
@@ -1866,10 +2565,24 @@ impl<Owner: DepType + 'static, ArgsType: DepEventArgs + 'static> Source for DepE
     ) -> HandledSource {
         let mut obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get_mut(state, self.id.into_raw());
         let entry = self.event.entry_mut(&mut obj);
-        let handler_id = entry.handlers.insert(|handler_id| (BoxedHandler(handler), handler_id));
+        let handler_id = (Reverse(self.priority), entry.seq);
+        entry.seq += 1;
+        entry.handlers.insert(handler_id, EventHandler { handler, handled_too: self.handled_too });
+        let handled_too = self.handled_too;
+        let init = entry.last.clone().filter(|args| handled_too || !args.handled()).map(|args| {
+            let event = self.event;
+            let id = self.id;
+            let init: Box<dyn FnOnce(&mut dyn State)> = Box::new(move |state: &mut dyn State| {
+                let obj = <Owner::Id as DepObj<Owner::DepObjKey, Owner>>::get(state, id.into_raw());
+                let entry = event.entry(&obj);
+                let handler = entry.handlers[&handler_id].handler.clone();
+                handler.execute(state, args.detach_handled());
+            });
+            init
+        });
         HandledSource {
             handler_id: Box::new(DepEventHandledSource { handler_id, id: self.id, event: self.event }),
-            init: None // TODO some events with cached value?
+            init
         }
     }
 }
@@ -1893,6 +2606,8 @@ impl<Owner: DepType, PropType: Convenient> HandlerId for DepPropHandledValueSour
             self.prop.update_parent_children_has_handlers(state, self.id.into_raw());
         }
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()) }
 }
 
 #[derive(Educe)]
@@ -1914,6 +2629,8 @@ impl<Owner: DepType, PropType: Convenient> HandlerId for DepPropHandledChangeIni
             self.prop.update_parent_children_has_handlers(state, self.id.into_raw());
         }
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()) }
 }
 
 #[derive(Educe)]
@@ -1935,6 +2652,8 @@ impl<Owner: DepType, PropType: Convenient> HandlerId for DepPropHandledChangeFin
             self.prop.update_parent_children_has_handlers(state, self.id.into_raw());
         }
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()) }
 }
 
 #[derive(Educe)]
@@ -1956,6 +2675,8 @@ impl<Owner: DepType, PropType: Convenient> HandlerId for DepPropHandledChangeSou
             self.prop.update_parent_children_has_handlers(state, self.id.into_raw());
         }
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Prop, self.id, self.prop.offset()) }
 }
 
 #[derive(Educe)]
@@ -2163,6 +2884,8 @@ impl<Owner: DepType, ItemType: Convenient> HandlerId for DepVecChangedHandledSou
         let entry_mut = self.vec.entry_mut(&mut obj);
         entry_mut.handlers.changed_handlers.remove(self.handler_id);
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()) }
 }
 
 #[derive(Educe)]
@@ -2184,6 +2907,8 @@ impl<Owner: DepType, ItemType: Convenient> HandlerId for DepVecItemHandledInitia
             x != dropping_binding
         }).map(|binding| binding.drop_self(state));
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()) }
 }
 
 #[derive(Educe)]
@@ -2206,6 +2931,8 @@ impl<Owner: DepType, ItemType: Convenient> HandlerId for DepVecItemHandledSource
             x != dropping_binding
         }).map(|binding| binding.drop_self(state));
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()) }
 }
 
 #[derive(Educe)]
@@ -2262,6 +2989,8 @@ impl<Owner: DepType, ItemType: Convenient> Target<()> for DepVecItemInitialFinal
     fn execute(&self, state: &mut dyn State, (): ()) {
         self.vec.modify(state, self.id, DepVecModification::Update(None));
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()) }
 }
 
 impl<Owner: DepType, ItemType: Convenient> Holder for DepVecItemInitialFinalSourceUpdate<Owner, ItemType> where
@@ -2273,6 +3002,8 @@ impl<Owner: DepType, ItemType: Convenient> Holder for DepVecItemInitialFinalSour
         let ok = entry.handlers.item_initial_final_handler.as_mut().unwrap().update.take().is_some();
         debug_assert!(ok);
     }
+
+    fn describe(&self) -> String { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()).to_string() }
 }
 
 #[derive(Educe)]
@@ -2289,6 +3020,8 @@ impl<Owner: DepType, ItemType: Convenient> Target<()> for DepVecItemSourceUpdate
     fn execute(&self, state: &mut dyn State, (): ()) {
         self.vec.modify(state, self.id, DepVecModification::Update(Some(self.handler_id)));
     }
+
+    fn describe(&self) -> SourceDescriptor { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()) }
 }
 
 impl<Owner: DepType, ItemType: Convenient> Holder for DepVecItemSourceUpdate<Owner, ItemType> where
@@ -2300,6 +3033,8 @@ impl<Owner: DepType, ItemType: Convenient> Holder for DepVecItemSourceUpdate<Own
         let ok = entry.handlers.item_handlers[self.handler_id].update.take().is_some();
         debug_assert!(ok);
     }
+
+    fn describe(&self) -> String { describe_member::<Owner>(MemberKind::Vec, self.id, self.vec.offset()).to_string() }
 }
 
 #[derive(Educe)]
@@ -3195,7 +3930,7 @@ macro_rules! dep_type_impl {
         [$($update_handlers:tt)*]
         [$BaseBuilder:ident] [$($bc_g:tt)*] [$($bc_r:tt)*] [$($bc_w:tt)*]
         [$($builder_methods:tt)*]
-        [[[bubble] $field:ident yield $field_ty:ty] $($fields:tt)*]
+        [[[$($event_attr:tt)*] $field:ident yield $field_ty:ty] $($fields:tt)*]
     ) => {
         $crate::dep_type_impl! {
             @unroll_fields
@@ -3207,7 +3942,7 @@ macro_rules! dep_type_impl {
             ]
             [
                 $($core_new)*
-                $field: $crate::DepEventEntry::new(true),
+                $field: $crate::dep_type_impl!(@event_entry [$($event_attr)*]),
             ]
             [
                 $($core_consts)*
@@ -3239,85 +3974,20 @@ macro_rules! dep_type_impl {
             [$($fields)*]
         }
     };
-    (
-        @unroll_fields
-        [$([$attr:meta])*] [$vis:vis] [$name:ident] [$id:ident] [$Id:ty] [$DepObjKey:ty]
-        [$state:ident] [$this:ident] [$bindings:ident] [$handlers:ident]
-        [$($g:tt)*] [$($r:tt)*] [$($w:tt)*]
-        [$($core_fields:tt)*]
-        [$($core_new:tt)*]
-        [$($core_consts:tt)*]
-        [$($dep_props:tt)*]
-        [$($core_bindings:tt)*]
-        [$($core_handlers:tt)*]
-        [$($update_handlers:tt)*]
-        [$BaseBuilder:ident] [$($bc_g:tt)*] [$($bc_r:tt)*] [$($bc_w:tt)*]
-        [$($builder_methods:tt)*]
-        [[[] $field:ident yield $field_ty:ty] $($fields:tt)*]
-    ) => {
-        $crate::dep_type_impl! {
-            @unroll_fields
-            [$([$attr])*] [$vis] [$name] [$id] [$Id] [$DepObjKey] [$state] [$this] [$bindings] [$handlers]
-            [$($g)*] [$($r)*] [$($w)*]
-            [
-                $($core_fields)*
-                $field: $crate::DepEventEntry<$field_ty>,
-            ]
-            [
-                $($core_new)*
-                $field: $crate::DepEventEntry::new(false),
-            ]
-            [
-                $($core_consts)*
-            ]
-            [
-                $($dep_props)*
-
-                $vis const [< $field:upper >] : $crate::DepEvent<Self, $field_ty> = {
-                    unsafe {
-                        let offset = $crate::memoffset_offset_of!( [< $name Core >] $($r)*, $field );
-                        $crate::DepEvent::new(offset)
-                    }
-                };
-            ]
-            [
-                $($core_bindings)*
-            ]
-            [
-                $($core_handlers)*
-                $this . $field .take_all_handlers($handlers);
-            ]
-            [
-                $($update_handlers)*
-            ]
-            [$BaseBuilder] [$($bc_g)*] [$($bc_r)*] [$($bc_w)*]
-            [
-                $($builder_methods)*
-            ]
-            [$($fields)*]
-        }
-    };
-    (
-        @unroll_fields
-        [$([$attr:meta])*] [$vis:vis] [$name:ident] [$id:ident] [$Id:ty] [$DepObjKey:ty]
-        [$state:ident] [$this:ident] [$bindings:ident] [$handlers:ident]
-        [$($g:tt)*] [$($r:tt)*] [$($w:tt)*]
-        [$($core_fields:tt)*]
-        [$($core_new:tt)*]
-        [$($core_consts:tt)*]
-        [$($dep_props:tt)*]
-        [$($core_bindings:tt)*]
-        [$($core_handlers:tt)*]
-        [$($update_handlers:tt)*]
-        [$BaseBuilder:ident] [$($bc_g:tt)*] [$($bc_r:tt)*] [$($bc_w:tt)*]
-        [$($builder_methods:tt)*]
-        [[[$($inherits:tt)*] $field:ident yield $field_ty:ty] $($fields:tt)*]
-    ) => {
+    (@event_entry []) => { $crate::DepEventEntry::new(false) };
+    (@event_entry [bubble]) => { $crate::DepEventEntry::new(true) };
+    (@event_entry [tunnel]) => { $crate::DepEventEntry::new_tunnel() };
+    (@event_entry [cached]) => { $crate::DepEventEntry::new_cached(false) };
+    (@event_entry [bubble cached]) => { $crate::DepEventEntry::new_cached(true) };
+    (@event_entry [cached bubble]) => { $crate::DepEventEntry::new_cached(true) };
+    (@event_entry [tunnel cached]) => { $crate::DepEventEntry::new_tunnel_cached() };
+    (@event_entry [cached tunnel]) => { $crate::DepEventEntry::new_tunnel_cached() };
+    (@event_entry [$($event_attr:tt)*]) => {
         $crate::std_compile_error!($crate::std_concat!(
             "invalid dep type event attributes: '",
-            $crate::std_stringify!($(#[$inherits])*),
-            "'; allowed attributes are: '#[bubble]'"
-        ));
+            $crate::std_stringify!($(#[$event_attr])*),
+            "'; allowed attributes are: '#[bubble]', '#[tunnel]', '#[cached]'"
+        ))
     };
     (
         @unroll_fields
@@ -4398,7 +5068,11 @@ macro_rules! impl_dep_obj_impl {
 #[cfg(test)]
 mod test {
     use alloc::borrow::Cow;
-    use components_arena::{Arena, Component, ComponentStop, NewtypeComponentId, with_arena_in_state_part};
+    use alloc::format;
+    use alloc::rc::Rc;
+    use core::any::type_name;
+    use core::cell::Cell;
+    use components_arena::{Arena, Component, ComponentId, ComponentStop, NewtypeComponentId, with_arena_in_state_part};
     use downcast_rs::{Downcast, impl_downcast};
     use dyn_context::{StateRefMut, Stop};
     use panicking::set_panicking_callback;
@@ -4450,7 +5124,8 @@ mod test {
     mod items {
         use alloc::borrow::Cow;
         use components_arena::{Arena, Component, ComponentStop, NewtypeComponentId, Id, with_arena_in_state_part};
-        use crate::{DetachedDepObjId, dep_type, impl_dep_obj};
+        use alloc::vec::Vec;
+        use crate::{DepObjId, Handled, dep_type, impl_dep_obj};
         use crate::binding::Binding3;
         use dyn_context::{SelfState, State, StateExt, Stop};
         use macro_attr_2018::macro_attr;
@@ -4459,6 +5134,8 @@ mod test {
             #[derive(Debug, Component!(stop=ItemStop))]
             struct ItemComponent {
                 props: ItemProps,
+                parent: Option<Item>,
+                children: Vec<Item>,
             }
         }
 
@@ -4475,12 +5152,33 @@ mod test {
             pub struct Item(Id<ItemComponent>);
         }
 
-        impl DetachedDepObjId for Item { }
+        impl DepObjId for Item {
+            fn parent(self, state: &dyn State) -> Option<Item> {
+                let items: &Items = state.get();
+                items.0[self.0].parent
+            }
+
+            fn next(self, state: &dyn State) -> Item {
+                let items: &Items = state.get();
+                let parent = if let Some(parent) = items.0[self.0].parent { parent } else { return self; };
+                let siblings = &items.0[parent.0].children;
+                let index = siblings.iter().position(|&x| x == self).unwrap();
+                siblings[(index + 1) % siblings.len()]
+            }
+
+            fn first_child(self, state: &dyn State) -> Option<Item> {
+                let items: &Items = state.get();
+                items.0[self.0].children.first().copied()
+            }
+        }
 
         impl Item {
             pub fn new(state: &mut dyn State) -> Item {
                 let items: &mut Items = state.get_mut();
-                let item = items.0.insert(|id| (ItemComponent { props: ItemProps::new_priv() }, Item(id)));
+                let item = items.0.insert(|id| (
+                    ItemComponent { props: ItemProps::new_priv(), parent: None, children: Vec::new() },
+                    Item(id)
+                ));
                 item.bind_weight(state);
                 item
             }
@@ -4492,6 +5190,14 @@ mod test {
                 items.0.remove(self.0);
             }
 
+            pub fn new_child(state: &mut dyn State, parent: Item) -> Item {
+                let item = Item::new(state);
+                let items: &mut Items = state.get_mut();
+                items.0[item.0].parent = Some(parent);
+                items.0[parent.0].children.push(item);
+                item
+            }
+
             fn bind_weight(self, state: &mut dyn State) {
                 let weight = Binding3::new(state, (), |(), base_weight, cursed, equipped| Some(
                     if equipped && cursed { base_weight + 100.0 } else { base_weight }
@@ -4526,6 +5232,12 @@ mod test {
                 weight: f32 = 0.0,
                 equipped: bool = false,
                 cursed: bool = false,
+                parent: Option<Item> = None,
+                #[bubble]
+                #[cached]
+                used yield Handled<i32>,
+                #[tunnel]
+                preview_used yield Handled<i32>,
             }
         }
     }
@@ -4543,6 +5255,13 @@ mod test {
         buf.unwrap()
     }
 
+    fn read_prop<PropType: Convenient>(state: &mut dyn State, item: Item, prop: DepProp<ItemProps, PropType>) -> PropType {
+        let binding = Binding1::new(state, (), |(), value| Some(value));
+        binding.set_source_1(state, &mut prop.value_source(item));
+        let value = binding.get_value(state);
+        binding.drop_self(state);
+        value.unwrap()
+    }
 
     #[test]
     fn apply_style() {
@@ -4556,4 +5275,1196 @@ mod test {
             Items::stop(state);
         }, &mut Bindings::new());
     }
+
+    #[test]
+    fn state_groups() {
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item = Item::new(state);
+            let names = Rc::new(Cell::new(0));
+            let name = Binding1::new(state, (), |(), name: Cow<'static, str>| Some(name));
+            name.set_target_fn(state, names.clone(), |_state, names, _| names.set(names.get() + 1));
+            name.set_source_1(state, &mut ItemProps::NAME.value_source(item));
+            let mut style = Style::new();
+            style.insert(ItemProps::NAME, Cow::Borrowed("styled"));
+            style.insert(ItemProps::BASE_WEIGHT, 1.0);
+            item.apply_style(state, Some(style.clone()));
+            let mut pressed = Style::new();
+            pressed.insert(ItemProps::BASE_WEIGHT, 2.0);
+            let mut equipped = Style::new();
+            equipped.insert(ItemProps::EQUIPPED, true);
+            let common = StateGroup::new("common")
+                .base(equipped)
+                .state("normal", Style::new())
+                .state("pressed", pressed);
+            let mut cursed = Style::new();
+            cursed.insert(ItemProps::CURSED, true);
+            let focus = StateGroup::new("focus")
+                .state("unfocused", Style::new())
+                .state("focused", cursed);
+            assert_eq!(names.get(), 2);
+            assert!(common.go_to_state(state, item, "pressed"));
+            assert!(!common.go_to_state(state, item, "pressed"));
+            assert!(focus.go_to_state(state, item, "focused"));
+            assert_eq!(common.current_state(state, item), Some("pressed"));
+            assert_eq!(focus.current_state(state, item), Some("focused"));
+            assert_eq!(read_prop(state, item, ItemProps::BASE_WEIGHT), 2.0);
+            assert!(read_prop(state, item, ItemProps::EQUIPPED));
+            assert!(read_prop(state, item, ItemProps::CURSED));
+            assert_eq!(read_name(state, item).as_ref(), "styled");
+            style.insert(ItemProps::BASE_WEIGHT, 3.0);
+            item.apply_style(state, Some(style));
+            assert_eq!(read_prop(state, item, ItemProps::BASE_WEIGHT), 2.0);
+            assert!(common.go_to_state(state, item, "normal"));
+            assert_eq!(read_prop(state, item, ItemProps::BASE_WEIGHT), 3.0);
+            assert!(read_prop(state, item, ItemProps::EQUIPPED));
+            assert!(read_prop(state, item, ItemProps::CURSED));
+            assert!(focus.go_to_state(state, item, "unfocused"));
+            assert!(!read_prop(state, item, ItemProps::CURSED));
+            assert_eq!(names.get(), 2);
+            name.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+        let mut style = Style::<ItemProps>::new();
+        style.insert(ItemProps::CURSED, true);
+        let mut other = Style::new();
+        other.insert(ItemProps::CURSED, false);
+        other.insert(ItemProps::EQUIPPED, true);
+        assert_eq!(style.merge(&other), Ok(()));
+        assert_eq!(style.len(), 2);
+    }
+
+    #[test]
+    fn parse_style() {
+        use crate::style_text::{StyleParseError, StyleParser};
+        let parser = StyleParser::new()
+            .prop("base_weight", ItemProps::BASE_WEIGHT)
+            .prop("cursed", ItemProps::CURSED);
+        let style = parser.parse("base_weight: 5.0;\ncursed: true;").unwrap();
+        assert_eq!(style.len(), 2);
+        assert!(style.contains_prop(ItemProps::BASE_WEIGHT));
+        assert!(style.contains_prop(ItemProps::CURSED));
+        assert_eq!(
+            parser.parse("base_weight: 5.0;\ncursed: maybe;").unwrap_err(),
+            StyleParseError::InvalidValue {
+                line: 2,
+                name: "cursed".into(),
+                value: "maybe".into(),
+                expected: "bool",
+                reason: "maybe".parse::<bool>().unwrap_err().to_string()
+            }
+        );
+        assert_eq!(
+            parser.parse("weight: 1.0").unwrap_err(),
+            StyleParseError::UnknownProp { line: 1, name: "weight".into() }
+        );
+    }
+
+    #[derive(Debug)]
+    struct CloneCounter(Rc<Cell<usize>>);
+
+    impl Clone for CloneCounter {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            CloneCounter(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn closures_are_not_cloned_on_evaluation() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let clones = Rc::new(Cell::new(0));
+        let counter = CloneCounter(clones.clone());
+        let subject = Subject::<i32>::new(state);
+        let binding = Binding1::new_closure(state, move |x: i32| {
+            let _ = &counter;
+            Some(x + 1)
+        });
+        binding.set_source_1(state, &mut subject.clone());
+        let counter = CloneCounter(clones.clone());
+        let ext = BindingExt1::new_closure(state, move |_state: &mut dyn State, x: i32| {
+            let _ = &counter;
+            Re::Yield(x * 2)
+        });
+        let last = Rc::new(Cell::new(None));
+        let target = last.clone();
+        ext.set_target_closure(state, move |_state, value| target.set(Some(value)));
+        ext.set_source_1(state, &mut binding.source());
+        subject.push(state, 1);
+        subject.push(state, 2);
+        assert_eq!(binding.get_value(state), Some(3));
+        assert_eq!(last.get(), Some(6));
+        assert_eq!(clones.get(), 0);
+        ext.drop_self(state);
+        binding.drop_self(state);
+        subject.drop_self(state);
+    }
+
+    #[test]
+    fn closure_storage() {
+        use crate::binding::n::Binding16;
+        let state: &mut dyn State = &mut Bindings::new();
+        let subjects: Vec<Subject<i32>> = (0 .. 16).map(|_| Subject::new(state)).collect();
+        let captured = Rc::new(());
+        let offset = captured.clone();
+        let binding = Binding16::new_closure(state, move |x1: i32, x2: i32, x3: i32, x4: i32, x5: i32, x6: i32, x7: i32, x8: i32, x9: i32, x10: i32, x11: i32, x12: i32, x13: i32, x14: i32, x15: i32, x16: i32| {
+            let _ = &offset;
+            Some(x1 + x2 + x3 + x4 + x5 + x6 + x7 + x8 + x9 + x10 + x11 + x12 + x13 + x14 + x15 + x16)
+        });
+        binding.set_source_1(state, &mut subjects[0].clone());
+        binding.set_source_2(state, &mut subjects[1].clone());
+        binding.set_source_3(state, &mut subjects[2].clone());
+        binding.set_source_4(state, &mut subjects[3].clone());
+        binding.set_source_5(state, &mut subjects[4].clone());
+        binding.set_source_6(state, &mut subjects[5].clone());
+        binding.set_source_7(state, &mut subjects[6].clone());
+        binding.set_source_8(state, &mut subjects[7].clone());
+        binding.set_source_9(state, &mut subjects[8].clone());
+        binding.set_source_10(state, &mut subjects[9].clone());
+        binding.set_source_11(state, &mut subjects[10].clone());
+        binding.set_source_12(state, &mut subjects[11].clone());
+        binding.set_source_13(state, &mut subjects[12].clone());
+        binding.set_source_14(state, &mut subjects[13].clone());
+        binding.set_source_15(state, &mut subjects[14].clone());
+        binding.set_source_16(state, &mut subjects[15].clone());
+        for (i, subject) in subjects.iter().enumerate() {
+            subject.push(state, i as i32);
+        }
+        assert_eq!(binding.get_value(state), Some(120));
+        binding.drop_self(state);
+        assert_eq!(Rc::strong_count(&captured), 1);
+        let small = captured.clone();
+        let inline = Binding1::new_closure(state, move |x: i32| {
+            let _ = &small;
+            Some(x + 1)
+        });
+        inline.set_source_1(state, &mut subjects[1].clone());
+        let large = ([7u64; 64], captured.clone());
+        let boxed = Binding1::new_closure(state, move |x: i32| {
+            let _ = &large.1;
+            Some(x + large.0.iter().sum::<u64>() as i32)
+        });
+        boxed.set_source_1(state, &mut subjects[1].clone());
+        subjects[1].push(state, 2);
+        assert_eq!(inline.get_value(state), Some(3));
+        assert_eq!(boxed.get_value(state), Some(450));
+        inline.drop_self(state);
+        boxed.drop_self(state);
+        assert_eq!(Rc::strong_count(&captured), 1);
+        for subject in subjects {
+            subject.drop_self(state);
+        }
+    }
+
+    #[test]
+    fn fallible_bindings() {
+        use alloc::string::String;
+        (&mut Dispatcher::new()).merge_mut_and_then(|state| {
+            let subject = Subject::<i32>::new(state);
+            let binding = Binding1::new_fallible(state, (), |(), x: i32| {
+                if x >= 0 { Ok(x) } else { Err(format!("negative {}", x)) }
+            });
+            let values = Rc::new(Cell::new(Vec::new()));
+            binding.set_target_fn(state, values.clone(), |_state, values, value| {
+                let mut list = values.take();
+                list.push(value);
+                values.set(list);
+            });
+            let errors: Rc<Cell<Vec<Option<String>>>> = Rc::new(Cell::new(Vec::new()));
+            binding.set_error_target_fn(state, errors.clone(), |_state, errors, error| {
+                let mut list = errors.take();
+                list.push(error);
+                errors.set(list);
+            });
+            binding.set_source_1(state, &mut subject.clone());
+            subject.push(state, 1);
+            subject.push(state, -2);
+            subject.push(state, -3);
+            subject.push(state, 4);
+            subject.push(state, 5);
+            assert_eq!(values.take(), [1, 4, 5]);
+            assert_eq!(errors.take(), [Some("negative -2".into()), Some("negative -3".into()), None]);
+            assert_eq!(binding.get_value(state), Some(5));
+            binding.set_schedule(state, BindingSchedule::Deferred(0));
+            subject.push(state, -6);
+            assert!(errors.take().is_empty());
+            Dispatcher::drain(state);
+            assert!(values.take().is_empty());
+            assert_eq!(errors.take(), [Some("negative -6".into())]);
+            let other = Subject::<i32>::new(state);
+            let sum = Binding2::new_fallible(state, (), |(), x: i32, y: i32| {
+                if x + y >= 0 { Ok(x + y) } else { Err(format!("negative {}", x + y)) }
+            });
+            sum.set_schedule(state, BindingSchedule::Deferred(0));
+            let sum_errors: Rc<Cell<Vec<Option<String>>>> = Rc::new(Cell::new(Vec::new()));
+            sum.set_error_target_fn(state, sum_errors.clone(), |_state, errors, error| {
+                let mut list = errors.take();
+                list.push(error);
+                errors.set(list);
+            });
+            sum.set_source_1(state, &mut subject.clone());
+            sum.set_source_2(state, &mut other.clone());
+            other.push(state, 1);
+            Dispatcher::drain(state);
+            assert_eq!(sum_errors.take(), [Some("negative -5".into())]);
+            subject.push(state, 2);
+            assert_eq!(sum.get_value(state), Some(3));
+            other.push(state, -8);
+            assert_eq!(sum.get_value(state), None);
+            assert!(sum_errors.take().is_empty());
+            other.drop_self(state);
+            Dispatcher::drain(state);
+            assert!(sum_errors.take().is_empty());
+            sum.drop_self(state);
+            binding.drop_self(state);
+            subject.drop_self(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn converters() {
+        use crate::convert::{Cast, ValueConverter};
+
+        #[derive(Debug, Clone)]
+        struct Positive;
+
+        impl ValueConverter<f32, bool> for Positive {
+            fn convert(&self, value: f32) -> Option<bool> { Some(value > 0.0) }
+
+            fn convert_back(&self, value: bool) -> Option<f32> { Some(if value { 1.0 } else { 0.0 }) }
+        }
+
+        #[derive(Debug, Clone)]
+        struct Sum;
+
+        impl ValueConverter<(i32, i32), i32> for Sum {
+            fn convert(&self, (a, b): (i32, i32)) -> Option<i32> { a.checked_add(b) }
+        }
+
+        assert_eq!(Cast::<f64, i32>::new().convert(-2.7), Some(-2));
+        assert_eq!(Cast::<f64, i32>::new().convert(f64::NAN), None);
+        assert_eq!(Cast::<f64, i32>::new().convert(1e10), None);
+        assert_eq!(Cast::<f64, u8>::new().convert(-1.0), None);
+        assert_eq!(Cast::<f64, i32>::new().convert_back(3), Some(3.0));
+        assert_eq!(Cast::<f64, f32>::new().convert(1e300), None);
+        assert_eq!(Cast::<f64, f32>::new().convert(0.5), Some(0.5));
+        assert_eq!(Cast::<i64, u8>::new().convert(300), None);
+
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item = Item::new(state);
+            ItemProps::BASE_WEIGHT.link_two_way(state, item, ItemProps::CURSED, item, Positive);
+            let values = |state: &mut dyn State| (
+                read_prop(state, item, ItemProps::BASE_WEIGHT),
+                read_prop(state, item, ItemProps::CURSED)
+            );
+            ItemProps::BASE_WEIGHT.set(state, item, 5.0).immediate();
+            assert_eq!(values(state), (5.0, true));
+            ItemProps::CURSED.set(state, item, false).immediate();
+            assert_eq!(values(state), (0.0, false));
+            ItemProps::BASE_WEIGHT.set(state, item, 7.0).immediate();
+            ItemProps::BASE_WEIGHT.set(state, item, 9.0).immediate();
+            assert_eq!(values(state), (9.0, true));
+            ItemProps::CURSED.set(state, item, false).immediate();
+            assert_eq!(values(state), (0.0, false));
+            ItemProps::CURSED.set(state, item, true).immediate();
+            assert_eq!(values(state), (1.0, true));
+            Items::stop(state);
+        }, &mut Bindings::new());
+
+        let state: &mut dyn State = &mut Bindings::new();
+        let a = Subject::<i32>::new(state);
+        let b = Subject::<i32>::new(state);
+        let sum = Binding2::new_converted(state, Sum);
+        sum.set_source_1(state, &mut a.clone());
+        sum.set_source_2(state, &mut b.clone());
+        let ext_sum = Rc::new(Cell::new(None));
+        let sum_ext = BindingExt2::new_converted(state, Sum);
+        sum_ext.set_target_fn(state, ext_sum.clone(), |_, ext_sum, value| ext_sum.set(Some(value)));
+        sum_ext.set_source_1(state, &mut a.clone());
+        sum_ext.set_source_2(state, &mut b.clone());
+        a.push(state, 2);
+        b.push(state, 3);
+        assert_eq!(sum.get_value(state), Some(5));
+        assert_eq!(ext_sum.get(), Some(5));
+        a.push(state, i32::MAX);
+        assert_eq!(sum.get_value(state), None);
+        sum.drop_self(state);
+        sum_ext.drop_self(state);
+        a.drop_self(state);
+        b.drop_self(state);
+    }
+
+    fn binding_info(state: &dyn State, binding: impl Into<AnyBindingBase>) -> BindingNodeInfo {
+        let binding = binding.into();
+        let bindings: &Bindings = state.get();
+        bindings.nodes().into_iter().find(|x| x.binding == binding).unwrap()
+    }
+
+    #[test]
+    fn binding_subscribers() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let subject = Subject::<i32>::new(state);
+        let upstream = Binding1::new(state, (), |(), x: i32| Some(x + 1));
+        upstream.set_source_1(state, &mut subject.clone());
+        let downstream_1 = Binding1::new(state, (), |(), x: i32| Some(x * 10));
+        downstream_1.set_source_1(state, &mut upstream.source());
+        let downstream_2 = Binding1::new(state, (), |(), x: i32| Some(x * 100));
+        downstream_2.set_source_1(state, &mut upstream.source());
+        subject.push(state, 1);
+        assert_eq!(downstream_1.get_value(state), Some(20));
+        assert_eq!(downstream_2.get_value(state), Some(200));
+        assert_eq!(binding_info(state, upstream).subscribers, 2);
+        downstream_1.drop_self(state);
+        assert_eq!(binding_info(state, upstream).subscribers, 1);
+        subject.push(state, 2);
+        assert_eq!(downstream_2.get_value(state), Some(300));
+        upstream.drop_self(state);
+        assert!(binding_info(state, downstream_2).sources.is_empty());
+        downstream_2.drop_self(state);
+        subject.drop_self(state);
+    }
+
+    #[test]
+    fn shared_binding_targets() {
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item_1 = Item::new(state);
+            let item_2 = Item::new(state);
+            let subject = Subject::<i32>::new(state);
+            let binding = BindingExt1::new(state, (), |_state, _param, value: i32| Re::Yield(value as f32 * 2.0));
+            ItemProps::BASE_WEIGHT.bind_target(state, item_1, binding);
+            ItemProps::BASE_WEIGHT.bind_target(state, item_2, binding);
+            binding.set_source_1(state, &mut subject.clone());
+            subject.push(state, 1);
+            assert_eq!(read_prop(state, item_1, ItemProps::BASE_WEIGHT) as i32, 2);
+            assert_eq!(read_prop(state, item_2, ItemProps::BASE_WEIGHT) as i32, 2);
+            ItemProps::BASE_WEIGHT.unbind(state, item_2);
+            subject.push(state, 2);
+            assert_eq!(read_prop(state, item_1, ItemProps::BASE_WEIGHT) as i32, 4);
+            assert_eq!(read_prop(state, item_2, ItemProps::BASE_WEIGHT) as i32, 2);
+            let target = BindingBase::from(binding).add_target(state, ItemProps::WEIGHT.target(item_2), None);
+            assert!(target.remove(state));
+            assert!(!target.remove(state));
+            let target = BindingBase::from(binding).add_target(state, ItemProps::WEIGHT.target(item_2), None);
+            binding.drop_self(state);
+            assert!(!target.remove(state));
+            subject.push(state, 3);
+            assert_eq!(read_prop(state, item_1, ItemProps::BASE_WEIGHT) as i32, 4);
+            subject.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn binding_vec_sources() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let subject_1 = Subject::<i32>::new(state);
+        let subject_2 = Subject::<i32>::new(state);
+        let sum = BindingVec::new(state, (), |(), values: &[i32]| Some(values.iter().sum::<i32>()));
+        let source_1 = sum.add_source(state, &mut subject_1.clone());
+        let source_2 = sum.add_source(state, &mut subject_2.clone());
+        subject_1.push(state, 1);
+        assert_eq!(sum.get_value(state), None);
+        subject_2.push(state, 2);
+        assert_eq!(sum.get_value(state), Some(3));
+        subject_2.push(state, 5);
+        assert_eq!(sum.get_value(state), Some(6));
+        assert!(sum.remove_source(state, source_2));
+        assert!(!sum.remove_source(state, source_2));
+        assert_eq!(sum.get_value(state), Some(1));
+        let other = BindingVec::new(state, (), |(), values: &[i32]| values.first().copied());
+        assert!(!other.remove_source(state, source_1));
+        other.drop_self(state);
+        sum.drop_self(state);
+        assert!(!sum.remove_source(state, source_1));
+        let scale = Rc::new(10);
+        let weighted = BindingVec::new_closure(state, {
+            let scale = scale.clone();
+            move |values: &[i32]| Some(values.iter().map(|x| x * *scale).collect::<Vec<_>>())
+        });
+        let subject_3 = Subject::<i32>::new(state);
+        subject_3.push(state, 3);
+        let source_1 = weighted.add_source(state, &mut subject_1.clone());
+        weighted.add_source(state, &mut subject_2.clone());
+        weighted.add_source(state, &mut subject_3.clone());
+        assert_eq!(weighted.get_value(state).as_deref(), Some(&[10, 50, 30][..]));
+        assert!(weighted.remove_source(state, source_1));
+        weighted.add_source(state, &mut subject_1.clone());
+        assert_eq!(weighted.get_value(state).as_deref(), Some(&[50, 30, 10][..]));
+        assert_eq!(Rc::strong_count(&scale), 2);
+        weighted.drop_self(state);
+        assert_eq!(Rc::strong_count(&scale), 1);
+        subject_3.drop_self(state);
+        subject_2.drop_self(state);
+        subject_1.drop_self(state);
+    }
+
+    #[test]
+    fn binding_scopes() {
+        extern crate std;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        let state: &mut dyn State = &mut Bindings::new();
+        let subject = Subject::<i32>::new(state);
+        let scope = BindingScope::new(state);
+        let (kept, dropped, nested) = scope.run(state, |state| {
+            let kept = Binding1::new(state, (), |(), x: i32| Some(x));
+            kept.set_source_1(state, &mut subject.clone());
+            let dropped = Binding1::<_, Subject<i32>, _>::new(state, (), |(), x: i32| Some(x));
+            let nested = BindingScope::new(state);
+            (kept, dropped, nested)
+        });
+        let nested_binding = nested.run(state, |state| Binding1::<_, Subject<i32>, _>::new(state, (), |(), x: i32| Some(x)));
+        dropped.drop_self(state);
+        nested.drop_self(state);
+        let bindings: &Bindings = state.get();
+        assert!(bindings.report_leaks().contains("members: 1\n"));
+        assert_eq!(bindings.nodes().len(), 2);
+        let res = catch_unwind(AssertUnwindSafe(|| scope.run(state, |_| panic!("scope callback panic"))));
+        assert!(res.is_err());
+        let outside = Binding1::<_, Subject<i32>, _>::new(state, (), |(), x: i32| Some(x));
+        subject.push(state, 1);
+        assert_eq!(kept.get_value(state), Some(1));
+        scope.drop_self(state);
+        assert!(!binding_info_exists(state, kept));
+        assert!(!binding_info_exists(state, nested_binding));
+        assert!(binding_info_exists(state, outside));
+        outside.drop_self(state);
+        subject.drop_self(state);
+    }
+
+    fn binding_info_exists(state: &dyn State, binding: impl Into<AnyBindingBase>) -> bool {
+        let binding = binding.into();
+        let bindings: &Bindings = state.get();
+        bindings.nodes().into_iter().any(|x| x.binding == binding)
+    }
+
+    #[test]
+    fn switch_follows_object_reference() {
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item_1 = Item::new(state);
+            let item_2 = Item::new(state);
+            let child = Item::new(state);
+            ItemProps::BASE_WEIGHT.set(state, item_1, 1.0).immediate();
+            ItemProps::BASE_WEIGHT.set(state, item_2, 2.0).immediate();
+            let parent_weight = Binding1::new(state, (), |(), weight: f32| Some(weight));
+            parent_weight.set_source_1(state, &mut ItemProps::PARENT.path_source(
+                child,
+                |parent| parent.map(|parent| ItemProps::BASE_WEIGHT.value_source(parent))
+            ));
+            assert_eq!(parent_weight.get_value(state), Some(0.0));
+            ItemProps::PARENT.set(state, child, Some(item_1)).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(1.0));
+            ItemProps::PARENT.set(state, child, Some(item_2)).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(2.0));
+            ItemProps::BASE_WEIGHT.set(state, item_1, 3.0).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(2.0));
+            ItemProps::PARENT.set(state, child, Some(item_1)).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(3.0));
+            ItemProps::BASE_WEIGHT.set(state, item_2, 4.0).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(3.0));
+            ItemProps::PARENT.set(state, child, None).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(0.0));
+            ItemProps::BASE_WEIGHT.set(state, item_1, 5.0).immediate();
+            assert_eq!(parent_weight.get_value(state), Some(0.0));
+            parent_weight.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn source_operators() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let events = Subject::<i32, NoCache>::new(state);
+        let doubled = Binding1::new(state, (), |(), x: Option<i32>| x);
+        let last = Rc::new(Cell::new(None));
+        let target = last.clone();
+        doubled.set_target_closure(state, move |_state, value| target.set(Some(value)));
+        doubled.set_source_1(state, &mut events.map(|x| x * 2));
+        events.push(state, 3);
+        assert_eq!(last.get(), Some(6));
+        assert_eq!(doubled.get_value(state), None);
+        let threshold = 10;
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let subject = Subject::<i32>::new(state);
+        let sum = Binding1::new(state, (), |(), x: i32| Some(x));
+        sum.set_source_1(state, &mut subject
+            .filter(move |&x| x < threshold)
+            .map(move |x| { counter.set(counter.get() + 1); x })
+            .distinct()
+            .scan(0, |acc, x| acc + x)
+        );
+        for x in [1, 20, 2, 2] {
+            subject.push(state, x);
+        }
+        assert_eq!(sum.get_value(state), Some(3));
+        assert_eq!(calls.get(), 3);
+        let bindings: &Bindings = state.get();
+        assert_eq!(bindings.nodes().len(), 4);
+        sum.drop_self(state);
+        subject.drop_self(state);
+        doubled.drop_self(state);
+        events.drop_self(state);
+    }
+
+    fn timed_log(
+        state: &mut dyn State,
+        source: impl FnOnce(Subject<i32, NoCache>) -> crate::binding::Timed<Subject<i32, NoCache>>
+    ) -> (Subject<i32, NoCache>, Binding1<(), crate::binding::Timed<Subject<i32, NoCache>>, i32>, Rc<Cell<Vec<(u64, i32)>>>) {
+        let subject = Subject::<i32, NoCache>::new(state);
+        let binding = Binding1::new(state, (), |(), x: Option<i32>| x);
+        let log = Rc::new(Cell::new(Vec::new()));
+        binding.set_target_fn(state, log.clone(), |state, log, value| {
+            let clock: &crate::clock::Clock = state.get();
+            let mut values = log.take();
+            values.push((clock.now(), value));
+            log.set(values);
+        });
+        binding.set_source_1(state, &mut source(subject));
+        (subject, binding, log)
+    }
+
+    #[test]
+    fn time_operators() {
+        use crate::clock::Clock;
+        (&mut Clock::new()).merge_mut_and_then(|state| {
+            let debounce = timed_log(state, |x| x.debounce(3));
+            let throttle = timed_log(state, |x| x.throttle(3));
+            let delay = timed_log(state, |x| x.delay(3));
+            let sample = timed_log(state, |x| x.sample(3));
+            for (time, value) in [(0, 1), (1, 2), (5, 3), (12, 4)] {
+                Clock::tick(state, time);
+                for (subject, _, _) in [&debounce, &throttle, &delay, &sample] {
+                    subject.push(state, value);
+                }
+            }
+            Clock::tick(state, 20);
+            assert_eq!(debounce.2.take(), [(4, 2), (8, 3), (15, 4)]);
+            assert_eq!(throttle.2.take(), [(0, 1), (5, 3), (12, 4)]);
+            assert_eq!(delay.2.take(), [(3, 1), (4, 2), (8, 3), (15, 4)]);
+            assert_eq!(sample.2.take(), [(3, 2), (8, 3), (15, 4)]);
+            delay.0.push(state, 5);
+            for (subject, binding, _) in [debounce, throttle, delay, sample] {
+                binding.drop_self(state);
+                subject.drop_self(state);
+            }
+            Clock::tick(state, 30);
+            let clock: &Clock = state.get();
+            assert_eq!(clock.now(), 30);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn clock_tick() {
+        use crate::clock::Clock;
+        let mut clock = Clock::new();
+        let fired = Rc::new(Cell::new(Vec::new()));
+        for (delay, name) in [(5, "b"), (2, "a"), (5, "c"), (9, "cancelled")] {
+            let fired = fired.clone();
+            let timer = clock.schedule(delay, Box::new(move |state, timer| {
+                let clock: &mut Clock = state.get_mut();
+                assert_eq!(clock.now(), timer.at());
+                let mut names = fired.take();
+                names.push((clock.now(), name));
+                fired.set(names);
+                if name == "a" {
+                    let fired = fired.clone();
+                    clock.schedule(1, Box::new(move |state, _| {
+                        let clock: &Clock = state.get();
+                        let mut names = fired.take();
+                        names.push((clock.now(), "nested"));
+                        fired.set(names);
+                    }));
+                }
+            }));
+            if name == "cancelled" {
+                assert!(clock.cancel(timer));
+                assert!(!clock.cancel(timer));
+            }
+        }
+        clock.merge_mut_and_then(|state| {
+            Clock::tick(state, 4);
+            assert_eq!(fired.take(), [(2, "a"), (3, "nested")]);
+            Clock::tick(state, 10);
+            assert_eq!(fired.take(), [(5, "b"), (5, "c")]);
+            let clock: &Clock = state.get();
+            assert_eq!(clock.now(), 10);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn time_operators_require_clock() {
+        extern crate std;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        let state: &mut dyn State = &mut Bindings::new();
+        let subject = Subject::<i32, NoCache>::new(state);
+        let binding = Binding1::new(state, (), |(), x: Option<i32>| x);
+        let res = catch_unwind(AssertUnwindSafe(|| binding.set_source_1(state, &mut subject.debounce(1))));
+        assert!(panic_message(res.unwrap_err()).contains("require the Clock state part"));
+        binding.drop_self(state);
+        subject.drop_self(state);
+    }
+
+    fn panic_message(payload: Box<dyn core::any::Any + Send>) -> String {
+        payload.downcast_ref::<String>().cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|x| x.to_string()))
+            .unwrap()
+    }
+
+    fn logged_binding(
+        state: &mut dyn State,
+        log: &Rc<Cell<Vec<(&'static str, i32)>>>,
+        name: &'static str,
+        subject: Subject<i32>,
+        schedule: BindingSchedule
+    ) -> Binding1<(), Subject<i32>, i32> {
+        let binding = Binding1::new(state, (), |(), x: i32| Some(x));
+        binding.set_schedule(state, schedule);
+        binding.set_target_fn(state, (log.clone(), name), |_state, (log, name), value| {
+            let mut values = log.take();
+            values.push((name, value));
+            log.set(values);
+        });
+        binding.set_source_1(state, &mut subject.clone());
+        binding
+    }
+
+    #[test]
+    fn dispatcher() {
+        (&mut Dispatcher::new()).merge_mut_and_then(|state| {
+            let log = Rc::new(Cell::new(Vec::new()));
+            let subject = Subject::<i32>::new(state);
+            let idle_subject = Subject::<i32>::new(state);
+            let low = logged_binding(state, &log, "low", subject, BindingSchedule::Deferred(0));
+            let idle = Binding1::new(state, (), |(), x: i32| Some(x));
+            idle.set_schedule(state, BindingSchedule::OnIdle);
+            let idle_log = log.clone();
+            idle.set_target_closure(state, move |state, value| {
+                let mut values = idle_log.take();
+                values.push(("idle", value));
+                idle_log.set(values);
+                idle_subject.push(state, value * 10);
+            });
+            idle.set_source_1(state, &mut subject.clone());
+            let high = logged_binding(state, &log, "high", subject, BindingSchedule::Deferred(5));
+            let low_2 = logged_binding(state, &log, "low_2", subject, BindingSchedule::Deferred(0));
+            let after_idle = logged_binding(state, &log, "after_idle", idle_subject, BindingSchedule::Deferred(9));
+            let dispatcher: &Dispatcher = state.get();
+            assert!(dispatcher.is_empty());
+            subject.push(state, 1);
+            subject.push(state, 2);
+            assert!(log.take().is_empty());
+            let dispatcher: &Dispatcher = state.get();
+            assert!(!dispatcher.is_empty());
+            Dispatcher::drain(state);
+            assert_eq!(log.take(), [("high", 2), ("low", 2), ("low_2", 2)]);
+            Dispatcher::drain(state);
+            assert!(log.take().is_empty());
+            subject.push(state, 3);
+            Dispatcher::drain_idle(state);
+            assert_eq!(log.take(), [("high", 3), ("low", 3), ("low_2", 3), ("idle", 3), ("after_idle", 30)]);
+            let dispatcher: &Dispatcher = state.get();
+            assert!(dispatcher.is_empty());
+            subject.push(state, 4);
+            low.drop_self(state);
+            Dispatcher::drain_idle(state);
+            assert_eq!(log.take(), [("high", 4), ("low_2", 4), ("idle", 4), ("after_idle", 40)]);
+            for binding in [high, low_2, after_idle] {
+                binding.drop_self(state);
+            }
+            idle.drop_self(state);
+            idle_subject.drop_self(state);
+            subject.drop_self(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn deferred_bindings_require_dispatcher() {
+        extern crate std;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        let state: &mut dyn State = &mut Bindings::new();
+        let log = Rc::new(Cell::new(Vec::new()));
+        let subject = Subject::<i32>::new(state);
+        let binding = logged_binding(state, &log, "deferred", subject, BindingSchedule::Deferred(0));
+        let res = catch_unwind(AssertUnwindSafe(|| subject.push(state, 1)));
+        assert!(panic_message(res.unwrap_err()).contains("require the Dispatcher state part"));
+        binding.drop_self(state);
+        subject.drop_self(state);
+    }
+
+    #[test]
+    fn paused_bindings() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let a = Subject::<i32>::new(state);
+        let b = Subject::<i32>::new(state);
+        let sum = Binding2::new(state, (), |(), a: i32, b: i32| Some(a + b));
+        let log = Rc::new(Cell::new(Vec::new()));
+        sum.set_target_fn(state, log.clone(), |_state, log, value| {
+            let mut values = log.take();
+            values.push(value);
+            log.set(values);
+        });
+        sum.set_source_1(state, &mut a.clone());
+        sum.set_source_2(state, &mut b.clone());
+        a.push(state, 1);
+        b.push(state, 2);
+        assert_eq!(log.take(), [3]);
+        assert!(sum.is_enabled(state));
+        sum.set_enabled(state, false);
+        assert!(!sum.is_enabled(state));
+        a.push(state, 10);
+        b.push(state, 20);
+        assert!(log.take().is_empty());
+        assert_eq!(sum.get_value(state), Some(30));
+        sum.set_enabled(state, true);
+        assert!(log.take().is_empty());
+        sum.set_enabled(state, false);
+        sum.resume(state);
+        assert!(sum.is_enabled(state));
+        assert_eq!(log.take(), [30]);
+        a.push(state, 100);
+        assert_eq!(log.take(), [120]);
+        sum.drop_self(state);
+        b.drop_self(state);
+        a.drop_self(state);
+    }
+
+    #[test]
+    fn subjects() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let log = Rc::new(Cell::new(Vec::new()));
+        let cached = Subject::<i32>::new(state);
+        assert_eq!(cached.get_value(state), None);
+        let early = logged_binding(state, &log, "early", cached, BindingSchedule::Immediate);
+        cached.push(state, 1);
+        assert_eq!(cached.get_value(state), Some(1));
+        assert_eq!(log.take(), [("early", 1)]);
+        let late = logged_binding(state, &log, "late", cached, BindingSchedule::Immediate);
+        assert_eq!(log.take(), [("late", 1)]);
+        assert_eq!(late.get_value(state), Some(1));
+        cached.push(state, 2);
+        assert_eq!(log.take(), [("early", 2), ("late", 2)]);
+        let uncached = Subject::<i32, NoCache>::new(state);
+        uncached.push(state, 1);
+        assert_eq!(uncached.get_value(state), None);
+        let values = Rc::new(Cell::new(Vec::new()));
+        let binding = Binding1::new(state, (), |(), x: Option<i32>| x);
+        binding.set_target_fn(state, values.clone(), |_state, values, value| {
+            let mut list = values.take();
+            list.push(value);
+            values.set(list);
+        });
+        binding.set_source_1(state, &mut uncached.clone());
+        assert!(values.take().is_empty());
+        uncached.push(state, 3);
+        assert_eq!(values.take(), [3]);
+        assert_eq!(binding.get_value(state), None);
+        late.drop_self(state);
+        cached.push(state, 4);
+        assert_eq!(log.take(), [("early", 4)]);
+        binding.drop_self(state);
+        uncached.drop_self(state);
+        early.drop_self(state);
+        cached.drop_self(state);
+    }
+
+    #[test]
+    fn leak_report() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let bindings: &Bindings = state.get();
+        assert_eq!(bindings.report_leaks(), "");
+        let subject = Subject::<i32>::new(state);
+        let scope = BindingScope::new(state);
+        let binding = scope.run(state, |state| {
+            let binding = Binding1::new(state, (), |(), x: i32| Some(x));
+            binding.set_source_1(state, &mut subject.clone());
+            binding
+        });
+        let bindings: &Bindings = state.get();
+        let report = bindings.report_leaks();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Subject<i32>"));
+        assert!(lines[0].ends_with(", sources: [], targets: []"));
+        assert!(lines[1].starts_with("Binding1<i32>"));
+        assert!(lines[1].ends_with(&format!(", sources: [{:?}], targets: []", AnyBindingBase::from(subject))));
+        assert!(lines[2].starts_with(&format!("{:?}", scope)));
+        assert!(lines[2].ends_with(", members: 1"));
+        for line in lines {
+            assert_eq!(line.contains(concat!(" created at ", file!())), cfg!(debug_assertions));
+        }
+        scope.drop_self(state);
+        assert!(!binding_info_exists(state, binding));
+        subject.drop_self(state);
+        let bindings: &Bindings = state.get();
+        assert_eq!(bindings.report_leaks(), "");
+    }
+
+    #[test]
+    fn class_handlers() {
+        set_panicking_callback(|| true);
+        let mut class_handlers = ClassHandlers::new();
+        class_handlers.add_prop_handler(ItemProps::EQUIPPED, |state, item, change| {
+            if change.new {
+                ItemProps::NAME.set(state, item, Cow::Borrowed("equipped")).immediate();
+            }
+        });
+        class_handlers.add_event_handler(ItemProps::USED, |_state, _item, args| {
+            if *args.args() < 0 { args.set_handled(); }
+        });
+        (&mut class_handlers).merge_mut_and_then(|state| (&mut Items::new()).merge_mut_and_then(|state| {
+            let item_1 = Item::new(state);
+            let item_2 = Item::new(state);
+            ItemProps::EQUIPPED.set(state, item_1, true).immediate();
+            assert_eq!(read_name(state, item_1).as_ref(), "equipped");
+            assert_eq!(read_name(state, item_2).as_ref(), "");
+            ItemProps::EQUIPPED.set(state, item_2, true).immediate();
+            assert_eq!(read_name(state, item_2).as_ref(), "equipped");
+            let used = Binding1::new(state, (), |(), args: Option<Handled<i32>>| args.map(|x| *x.args()));
+            let handled: Rc<Cell<Vec<i32>>> = Rc::new(Cell::new(Vec::new()));
+            used.set_target_fn(state, handled.clone(), |_state, handled, value| {
+                let mut values = handled.take();
+                values.push(value);
+                handled.set(values);
+            });
+            used.set_source_1(state, &mut ItemProps::USED.source(item_1));
+            ItemProps::USED.raise(state, item_1, Handled::new(1)).immediate();
+            ItemProps::USED.raise(state, item_1, Handled::new(-1)).immediate();
+            assert_eq!(handled.take(), [1]);
+            assert!(ClassHandlers::prop_handlers(state, ItemProps::NAME).is_none());
+            (&mut ClassHandlers::new()).merge_mut_and_then(|state| {
+                assert!(ClassHandlers::prop_handlers(state, ItemProps::EQUIPPED).is_none());
+            }, &mut Bindings::new());
+            used.drop_self(state);
+            Items::stop(state);
+        }, state), &mut Bindings::new());
+        let state: &mut dyn State = &mut Bindings::new();
+        assert!(ClassHandlers::prop_handlers(state, ItemProps::EQUIPPED).is_none());
+        let local = String::from("local");
+        fn type_id_of_val<T: ?Sized>(_: &T) -> TypeId { owner_type_id::<T>() }
+        assert_eq!(type_id_of_val(&local.as_str()), TypeId::of::<&'static str>());
+        assert_ne!(owner_type_id::<ItemProps>(), owner_type_id::<Item>());
+    }
+
+    fn log_event(
+        state: &mut dyn State,
+        log: &Rc<Cell<Vec<(&'static str, i32)>>>,
+        name: &'static str,
+        mut source: DepEventSource<ItemProps, Handled<i32>>,
+        handle: bool
+    ) -> Binding1<(&'static str, bool), DepEventSource<ItemProps, Handled<i32>>, (&'static str, i32)> {
+        let binding = Binding1::new(state, (name, handle), |(name, handle), args: Option<Handled<i32>>| args.map(|args| {
+            if handle { args.set_handled(); }
+            (name, *args.args())
+        }));
+        binding.set_target_fn(state, log.clone(), |_state, log, value| {
+            let mut values = log.take();
+            values.push(value);
+            log.set(values);
+        });
+        binding.set_source_1(state, &mut source);
+        binding
+    }
+
+    #[test]
+    fn event_routing() {
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let parent = Item::new(state);
+            let child = Item::new_child(state, parent);
+            let log = Rc::new(Cell::new(Vec::new()));
+            let mut bindings = Vec::from([
+                log_event(state, &log, "parent", ItemProps::USED.source(parent), false),
+                log_event(state, &log, "low", ItemProps::USED.source(child).with_priority(-1), false),
+                log_event(state, &log, "high", ItemProps::USED.source(child).with_priority(1), true),
+                log_event(state, &log, "too", ItemProps::USED.source_handled_too(child), false),
+                log_event(state, &log, "normal", ItemProps::USED.source(child), false),
+            ]);
+            ItemProps::USED.raise(state, child, Handled::new(1)).immediate();
+            assert_eq!(log.take(), [("high", 1), ("too", 1)]);
+            bindings.remove(2).drop_self(state);
+            ItemProps::USED.raise(state, child, Handled::new(2)).immediate();
+            assert_eq!(log.take(), [("too", 2), ("normal", 2), ("low", 2), ("parent", 2)]);
+            for binding in bindings {
+                binding.drop_self(state);
+            }
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn handled_flag_is_per_raise() {
+        extern crate std;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item = Item::new(state);
+            let log = Rc::new(Cell::new(Vec::new()));
+            let bindings = [
+                log_event(state, &log, "preview", ItemProps::PREVIEW_USED.source(item), true),
+                log_event(state, &log, "handler", ItemProps::USED.source(item).with_priority(1), true),
+                log_event(state, &log, "too", ItemProps::USED.source_handled_too(item), false),
+                log_event(state, &log, "normal", ItemProps::USED.source(item), false),
+            ];
+            let args = Handled::new(1);
+            ItemProps::USED.raise(state, item, args.clone()).immediate();
+            ItemProps::USED.raise(state, item, args.clone()).immediate();
+            assert!(!args.handled());
+            assert_eq!(log.take(), [("handler", 1), ("too", 1), ("handler", 1), ("too", 1)]);
+            let handled = args.detach_handled();
+            handled.set_handled();
+            assert!(handled == args);
+            ItemProps::USED.raise_with_preview(state, item, ItemProps::PREVIEW_USED, args).immediate();
+            assert_eq!(log.take(), [("preview", 1), ("too", 1)]);
+            let res = catch_unwind(AssertUnwindSafe(|| {
+                ItemProps::USED.raise_with_preview(state, item, ItemProps::USED, Handled::new(2)).immediate()
+            }));
+            assert!(panic_message(res.unwrap_err()).contains("should be '#[tunnel]'"));
+            assert!(log.take().is_empty());
+            for binding in bindings {
+                binding.drop_self(state);
+            }
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn cached_events() {
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let parent = Item::new(state);
+            let child = Item::new_child(state, parent);
+            let log = Rc::new(Cell::new(Vec::new()));
+            ItemProps::USED.raise(state, child, Handled::new(1)).immediate();
+            let mut bindings = Vec::from([
+                log_event(state, &log, "parent", ItemProps::USED.source(parent), false),
+                log_event(state, &log, "child", ItemProps::USED.source(child), true),
+            ]);
+            assert_eq!(log.take(), [("child", 1)]);
+            ItemProps::USED.raise(state, child, Handled::new(2)).immediate();
+            assert_eq!(log.take(), [("child", 2)]);
+            bindings.push(log_event(state, &log, "late", ItemProps::USED.source(child), false));
+            bindings.push(log_event(state, &log, "too", ItemProps::USED.source_handled_too(child), false));
+            assert_eq!(log.take(), [("too", 2)]);
+            for binding in bindings {
+                binding.drop_self(state);
+            }
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn consistent_diamond() {
+        let state: &mut dyn State = &mut Bindings::new();
+        let mut subject = Subject::<i32>::new(state);
+        let sum = Binding2::new(state, (), |(), a: i32, b: i32| Some(a + b));
+        sum.set_schedule(state, BindingSchedule::Consistent);
+        let updates: Rc<Cell<Vec<i32>>> = Rc::new(Cell::new(Vec::new()));
+        sum.set_target_fn(state, updates.clone(), |_state, updates, value| {
+            let mut values = updates.take();
+            values.push(value);
+            updates.set(values);
+        });
+        let doubled = Binding1::new(state, (), |(), x: i32| Some(2 * x));
+        doubled.set_schedule(state, BindingSchedule::Consistent);
+        let tripled = Binding1::new(state, (), |(), x: i32| Some(3 * x / 2));
+        tripled.set_schedule(state, BindingSchedule::Consistent);
+        subject.push(state, 1);
+        doubled.set_source_1(state, &mut subject);
+        tripled.set_source_1(state, &mut doubled.source());
+        sum.set_source_1(state, &mut subject);
+        sum.set_source_2(state, &mut tripled.source());
+        assert_eq!(updates.take(), [4]);
+        subject.push(state, 2);
+        assert_eq!(updates.take(), [8]);
+        subject.push(state, 4);
+        assert_eq!(updates.take(), [16]);
+        sum.drop_self(state);
+        doubled.drop_self(state);
+        tripled.drop_self(state);
+        subject.drop_self(state);
+    }
+
+    #[test]
+    fn consistent_bindings_survive_panics() {
+        extern crate std;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item = Item::new(state);
+            let unlucky = Binding1::new(state, (), |(), weight: f32| Some(weight));
+            unlucky.set_target_fn(state, (), |_state, (), weight| assert!(weight != 13.0, "unlucky weight"));
+            unlucky.set_source_1(state, &mut ItemProps::BASE_WEIGHT.value_source(item));
+            let sum = Binding2::new(state, (), |(), a: f32, b: f32| Some(a + b));
+            sum.set_schedule(state, BindingSchedule::Consistent);
+            let updates: Rc<Cell<Vec<f32>>> = Rc::new(Cell::new(Vec::new()));
+            sum.set_target_fn(state, updates.clone(), |_state, updates, value| {
+                let mut values = updates.take();
+                values.push(value);
+                updates.set(values);
+            });
+            let doubled = Binding1::new(state, (), |(), x: f32| Some(2.0 * x));
+            doubled.set_schedule(state, BindingSchedule::Consistent);
+            doubled.set_source_1(state, &mut ItemProps::BASE_WEIGHT.value_source(item));
+            sum.set_source_1(state, &mut ItemProps::BASE_WEIGHT.value_source(item));
+            sum.set_source_2(state, &mut doubled.source());
+            assert_eq!(updates.take(), [0.0]);
+            let res = catch_unwind(AssertUnwindSafe(|| ItemProps::BASE_WEIGHT.set(state, item, 13.0).immediate()));
+            assert!(res.is_err());
+            assert_eq!(updates.take(), []);
+            ItemProps::BASE_WEIGHT.set(state, item, 2.0).immediate();
+            assert_eq!(updates.take(), [6.0]);
+            sum.drop_self(state);
+            doubled.drop_self(state);
+            unlucky.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn deny_cycles() {
+        extern crate std;
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        use std::string::String;
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item = Item::new(state);
+            let bindings: &mut Bindings = state.get_mut();
+            bindings.set_deny_cycles(true);
+            let observer = Binding1::new(state, (), |(), weight: f32| Some(weight));
+            observer.set_source_1(state, &mut ItemProps::WEIGHT.value_source(item));
+            let feedback = Binding1::new(state, (), |(), weight: f32| Some(weight / 2.0));
+            ItemProps::BASE_WEIGHT.bind(state, item, feedback);
+            let res = catch_unwind(AssertUnwindSafe(|| {
+                feedback.set_source_1(state, &mut ItemProps::WEIGHT.value_source(item));
+            }));
+            let message = res.unwrap_err().downcast::<String>().unwrap();
+            assert!(message.starts_with("binding cycle: "));
+            assert!(message.contains(&format!("{:?}", AnyBindingBase::from(feedback))));
+            feedback.drop_self(state);
+            ItemProps::BASE_WEIGHT.set(state, item, 3.0).immediate();
+            assert_eq!(observer.get_value(state), Some(3.0));
+            observer.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn binding_graph_introspection() {
+        set_panicking_callback(|| true);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let item = Item::new(state);
+            let scope = BindingScope::new(state);
+            let observer = scope.run(state, |state| {
+                let observer = Binding1::new(state, (), |(), weight: f32| Some(weight));
+                observer.set_source_1(state, &mut ItemProps::WEIGHT.value_source(item).distinct());
+                observer
+            });
+            let prop = |offset| SourceDescriptor::Member {
+                kind: MemberKind::Prop,
+                owner: type_name::<ItemProps>(),
+                offset,
+                id: item.into_raw()
+            };
+            let bindings: &Bindings = state.get();
+            let nodes = bindings.nodes();
+            assert_eq!(nodes.len(), 2);
+            let weight = nodes.iter().find(|x| x.targets == [prop(ItemProps::WEIGHT.offset())]).unwrap();
+            assert_eq!(weight.kind, "Binding3");
+            assert!(weight.sources.contains(&prop(ItemProps::BASE_WEIGHT.offset())));
+            assert_eq!(binding_info(state, observer).sources, [prop(ItemProps::WEIGHT.offset())]);
+            let edge = format!("\"{}\" -> \"{:?}\";", prop(ItemProps::WEIGHT.offset()), AnyBindingBase::from(observer));
+            assert!(bindings.to_dot().contains(&edge));
+            scope.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+    }
+
+    #[test]
+    fn record_and_replay() {
+        extern crate std;
+        use components_arena::RawId;
+        use crate::recorder::{RecordFormat, Recorder};
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+        set_panicking_callback(|| true);
+        let format = RecordFormat::new()
+            .prop("base_weight", ItemProps::BASE_WEIGHT)
+            .prop("weight", ItemProps::WEIGHT)
+            .prop("cursed", ItemProps::CURSED)
+            .prop("equipped", ItemProps::EQUIPPED)
+            .event("used", ItemProps::USED);
+        let raw = |item: Item| { let id = item.into_raw(); format!("{}:{}", id.0, id.1) };
+        let mut recorder = Recorder::new();
+        let (recorded, text) = (&mut recorder).merge_mut_and_then(|state| (&mut Items::new()).merge_mut_and_then(|state| {
+            let parent = Item::new(state);
+            let child = Item::new_child(state, parent);
+            let subject = Subject::<bool>::new(state);
+            let equipped = Binding1::new(state, (), |(), x| Some(x));
+            ItemProps::EQUIPPED.bind(state, child, equipped);
+            equipped.set_source_1(state, &mut subject.clone());
+            let panicking = Binding1::new(state, (), |(), x: f32| Some(x));
+            panicking.set_target_fn(state, (), |_state, (), x| assert!(x != 13.0, "unlucky weight"));
+            panicking.set_source_1(state, &mut ItemProps::BASE_WEIGHT.value_source(parent));
+            ItemProps::BASE_WEIGHT.set(state, child, 5.0).immediate();
+            ItemProps::CURSED.set(state, child, true).immediate();
+            subject.push(state, true);
+            ItemProps::CURSED.unset(state, child).immediate();
+            ItemProps::USED.raise(state, child, Handled::new(3)).immediate();
+            let res = catch_unwind(AssertUnwindSafe(|| ItemProps::BASE_WEIGHT.set(state, parent, 13.0).immediate()));
+            assert!(res.is_err());
+            ItemProps::CURSED.set(state, parent, true).immediate();
+            let recorder: &mut Recorder = state.get_mut();
+            let text = format.write(recorder.records()).unwrap();
+            let external: Vec<_> = text.lines().filter(|x| !x.starts_with("driven ")).collect();
+            assert_eq!(external, [
+                format!("set {} base_weight 5", raw(child)),
+                format!("set {} cursed true", raw(child)),
+                format!("unset {} cursed", raw(child)),
+                format!("raise {} used 3", raw(child)),
+                format!("set {} base_weight 13", raw(parent)),
+                format!("set {} cursed true", raw(parent)),
+            ]);
+            assert!(text.contains(&format!("driven set {} equipped true\n", raw(child))));
+            assert!(!text.contains("weight 105"));
+            equipped.drop_self(state);
+            panicking.drop_self(state);
+            subject.drop_self(state);
+            Items::stop(state);
+            ([parent.into_raw(), child.into_raw()], text)
+        }, state), &mut Bindings::new());
+        let records = format.parse(&text).unwrap();
+        assert!(records.iter().all(|x| x.owner_type() == TypeId::of::<ItemProps>()));
+        assert_eq!(format.write(&records).unwrap(), text);
+        (&mut Items::new()).merge_mut_and_then(|state| {
+            let parent = Item::new(state);
+            let child = Item::new_child(state, parent);
+            let replayed: [RawId; 2] = [parent.into_raw(), child.into_raw()];
+            Recorder::replay_mapped(&records, state, |id| replayed[recorded.iter().position(|&x| x == id).unwrap()]);
+            assert_eq!(read_prop(state, child, ItemProps::BASE_WEIGHT), 5.0);
+            assert!(!read_prop(state, child, ItemProps::CURSED));
+            assert!(read_prop(state, child, ItemProps::EQUIPPED));
+            assert_eq!(read_prop(state, parent, ItemProps::BASE_WEIGHT), 13.0);
+            assert!(read_prop(state, parent, ItemProps::CURSED));
+            let log = Rc::new(Cell::new(Vec::new()));
+            let used = log_event(state, &log, "used", ItemProps::USED.source(child), false);
+            assert_eq!(log.take(), [("used", 3)]);
+            used.drop_self(state);
+            Items::stop(state);
+        }, &mut Bindings::new());
+        assert!(matches!(
+            format.parse("set 0:1 base_weight heavy"),
+            Err(crate::recorder::RecordParseError::InvalidValue { line: 1, .. })
+        ));
+        assert_eq!(
+            format.parse("set 0:1 base_weight  5").err().map(|x| x.to_string()),
+            Some("line 1: invalid 'base_weight' value ' 5', expected f32".into())
+        );
+        assert!(matches!(
+            format.parse("\nraise 0 used 1"),
+            Err(crate::recorder::RecordParseError::InvalidId { line: 2, .. })
+        ));
+    }
 }
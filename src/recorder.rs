@@ -0,0 +1,598 @@
+//! Recording and replaying dependency object mutations.
+//!
+//! If the state contains a [`Recorder`] part, every [`DepProp::set`], [`DepProp::unset`],
+//! and [`DepEvent::raise`] call made outside of other recorded calls gets logged.
+//! Mutations caused by recorded ones (e.g. through bindings or handlers) are not logged,
+//! because they are reproduced when the log is replayed.
+//!
+//! Mutations made by binding targets outside of recorded calls (e.g. when a source is attached,
+//! or from [`Clock::tick`](crate::clock::Clock::tick),
+//! [`Dispatcher::drain`](crate::binding::Dispatcher::drain),
+//! or [`Subject::push`](crate::binding::Subject::push)) are logged too,
+//! but marked as [driven](Record::is_driven), so an application, which recreates its bindings
+//! and drives its clock, dispatcher, and subjects while replaying, can skip them.
+//!
+//! A log can be written to text and parsed back with a [`RecordFormat`].
+//! Arena ids contain a random guard, so objects created in a fresh state get ids
+//! different from the logged ones even if they are created in the same order.
+//! Use [`Recorder::replay_mapped`] to replay such a log.
+
+use crate::{Convenient, DepEvent, DepEventArgs, DepObj, DepProp, DepType, owner_type_id};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use components_arena::{ComponentId, RawId};
+use core::any::{Any, TypeId, type_name};
+use core::fmt::{self, Debug, Display, Formatter, Write};
+use core::mem::take;
+use core::num::NonZeroUsize;
+use core::str::FromStr;
+use dyn_clone::{DynClone, clone_trait_object};
+use dyn_context::{SelfState, State};
+use educe::Educe;
+
+/// A recorded mutation kind.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum RecordKind {
+    Set,
+    Unset,
+    Raise,
+}
+
+/// A recorded mutation.
+pub trait Record: Debug + Display + DynClone {
+    fn kind(&self) -> RecordKind;
+
+    /// The dependency type id.
+    fn owner_type(&self) -> TypeId;
+
+    /// The dependency type name, used for display only.
+    fn owner(&self) -> &'static str;
+
+    /// The property or event offset in the dependency type.
+    fn offset(&self) -> usize;
+
+    fn id(&self) -> RawId;
+
+    /// The property value or the event args. Returns `None` for [`RecordKind::Unset`].
+    fn value(&self) -> Option<&dyn Any>;
+
+    /// Returns `true` if the mutation was made by a binding evaluated outside of a recorded call.
+    fn is_driven(&self) -> bool;
+
+    /// Repeats the mutation on the object with the specified id.
+    fn replay(&self, state: &mut dyn State, id: RawId);
+}
+
+clone_trait_object!(Record);
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct DepPropRecord<PropType: Convenient> {
+    owner_type: TypeId,
+    owner: &'static str,
+    offset: usize,
+    id: RawId,
+    value: Option<PropType>,
+    driven: bool,
+    #[educe(Debug(ignore))]
+    replay: fn(state: &mut dyn State, id: RawId, offset: usize, value: Option<PropType>),
+}
+
+impl<PropType: Convenient> Display for DepPropRecord<PropType> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.driven { write!(f, "driven ")?; }
+        if let Some(value) = self.value.as_ref() {
+            write!(f, "{:?}: {}+{} = {:?}", self.id, self.owner, self.offset, value)
+        } else {
+            write!(f, "{:?}: {}+{} unset", self.id, self.owner, self.offset)
+        }
+    }
+}
+
+impl<PropType: Convenient> Record for DepPropRecord<PropType> {
+    fn kind(&self) -> RecordKind {
+        if self.value.is_some() { RecordKind::Set } else { RecordKind::Unset }
+    }
+
+    fn owner_type(&self) -> TypeId { self.owner_type }
+
+    fn owner(&self) -> &'static str { self.owner }
+
+    fn offset(&self) -> usize { self.offset }
+
+    fn id(&self) -> RawId { self.id }
+
+    fn value(&self) -> Option<&dyn Any> { self.value.as_ref().map(|x| x as _) }
+
+    fn is_driven(&self) -> bool { self.driven }
+
+    fn replay(&self, state: &mut dyn State, id: RawId) {
+        (self.replay)(state, id, self.offset, self.value.clone());
+    }
+}
+
+fn replay_prop<Owner: DepType, PropType: Convenient>(
+    state: &mut dyn State,
+    id: RawId,
+    offset: usize,
+    value: Option<PropType>
+) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+    let prop = unsafe { DepProp::<Owner, PropType>::new(offset) };
+    if let Some(value) = value {
+        prop.set(state, Owner::Id::from_raw(id), value).immediate();
+    } else {
+        prop.unset(state, Owner::Id::from_raw(id)).immediate();
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug, Clone)]
+struct DepEventRecord<ArgsType: DepEventArgs> {
+    owner_type: TypeId,
+    owner: &'static str,
+    offset: usize,
+    id: RawId,
+    args: ArgsType,
+    driven: bool,
+    #[educe(Debug(ignore))]
+    replay: fn(state: &mut dyn State, id: RawId, offset: usize, args: ArgsType),
+}
+
+impl<ArgsType: DepEventArgs> Display for DepEventRecord<ArgsType> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.driven { write!(f, "driven ")?; }
+        write!(f, "{:?}: {}+{} raise {:?}", self.id, self.owner, self.offset, self.args)
+    }
+}
+
+impl<ArgsType: DepEventArgs> Record for DepEventRecord<ArgsType> {
+    fn kind(&self) -> RecordKind { RecordKind::Raise }
+
+    fn owner_type(&self) -> TypeId { self.owner_type }
+
+    fn owner(&self) -> &'static str { self.owner }
+
+    fn offset(&self) -> usize { self.offset }
+
+    fn id(&self) -> RawId { self.id }
+
+    fn value(&self) -> Option<&dyn Any> { Some(&self.args) }
+
+    fn is_driven(&self) -> bool { self.driven }
+
+    fn replay(&self, state: &mut dyn State, id: RawId) {
+        (self.replay)(state, id, self.offset, self.args.detach_handled());
+    }
+}
+
+fn replay_event<Owner: DepType, ArgsType: DepEventArgs>(
+    state: &mut dyn State,
+    id: RawId,
+    offset: usize,
+    args: ArgsType
+) where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+    let event = unsafe { DepEvent::<Owner, ArgsType>::new(offset) };
+    event.raise(state, Owner::Id::from_raw(id), args).immediate();
+}
+
+/// An in-memory mutations log.
+///
+/// Records implement [`Display`], so the log can be printed with a line per record
+/// to examine it. Use a [`RecordFormat`] to save a log and load it back.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    records: Vec<Box<dyn Record>>,
+    recording: bool,
+    depth: usize,
+    driven: usize,
+}
+
+impl SelfState for Recorder { }
+
+impl const Default for Recorder {
+    fn default() -> Self { Recorder::new() }
+}
+
+/// Leaves a recorded or driven mutation when dropped, even if unwinding.
+///
+/// The guarded code accesses the state through a reference derived from the `state` pointer.
+/// It is sound as long as the original reference is not used after the guard creation,
+/// because the guard dereferences the pointer only in `drop`, after the derived reference is gone.
+struct RecorderGuard {
+    state: *mut dyn State,
+    driven: bool,
+}
+
+impl Drop for RecorderGuard {
+    fn drop(&mut self) {
+        // SAFETY: see the type documentation.
+        let state = unsafe { &mut *self.state };
+        let recorder = state.get_mut_raw(TypeId::of::<Recorder>()).unwrap();
+        let recorder = recorder.downcast_mut::<Recorder>().unwrap();
+        if self.driven {
+            recorder.driven -= 1;
+        } else {
+            recorder.depth -= 1;
+        }
+    }
+}
+
+impl Recorder {
+    pub const fn new() -> Self {
+        Recorder { records: Vec::new(), recording: true, depth: 0, driven: 0 }
+    }
+
+    pub fn is_recording(&self) -> bool { self.recording }
+
+    /// Pauses or resumes recording.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    pub fn records(&self) -> &[Box<dyn Record>] { &self.records }
+
+    pub fn take_records(&mut self) -> Vec<Box<dyn Record>> { take(&mut self.records) }
+
+    pub fn clear(&mut self) { self.records.clear(); }
+
+    /// Replays records in the logged order.
+    pub fn replay(records: &[Box<dyn Record>], state: &mut dyn State) {
+        Self::replay_mapped(records, state, |id| id);
+    }
+
+    /// Replays records in the logged order, passing logged ids through `map`.
+    ///
+    /// Allows to replay a log in a state, where objects got other ids.
+    pub fn replay_mapped(records: &[Box<dyn Record>], state: &mut dyn State, mut map: impl FnMut(RawId) -> RawId) {
+        for record in records {
+            record.replay(state, map(record.id()));
+        }
+    }
+
+    /// Records the mutation, if it is not caused by another recorded one, and enters it.
+    ///
+    /// Returns `false` if there is no recorder in the state.
+    fn enter(state: &mut dyn State, record: impl FnOnce(bool) -> Box<dyn Record>) -> bool {
+        let recorder = if let Some(recorder) = state.get_mut_raw(TypeId::of::<Recorder>()) {
+            recorder.downcast_mut::<Recorder>().unwrap()
+        } else {
+            return false;
+        };
+        if recorder.depth == 0 && recorder.recording {
+            let driven = recorder.driven != 0;
+            recorder.records.push(record(driven));
+        }
+        recorder.depth += 1;
+        true
+    }
+
+    /// Calls `f` with `value`, recording the property mutation.
+    ///
+    /// The nesting depth is restored even if `f` panics.
+    pub(crate) fn run_prop<Owner: DepType, PropType: Convenient, R>(
+        state: &mut dyn State,
+        id: Owner::Id,
+        prop: DepProp<Owner, PropType>,
+        value: Option<PropType>,
+        f: impl FnOnce(&mut dyn State, Option<PropType>) -> R
+    ) -> R where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        let entered = Self::enter(state, |driven| Box::new(DepPropRecord {
+            owner_type: owner_type_id::<Owner>(),
+            owner: type_name::<Owner>(),
+            offset: prop.offset(),
+            id: id.into_raw(),
+            value: value.clone(),
+            driven,
+            replay: replay_prop::<Owner, PropType>,
+        }));
+        if !entered { return f(state, value); }
+        let guard = RecorderGuard { state, driven: false };
+        // SAFETY: `state` is not used after the guard creation.
+        f(unsafe { &mut *guard.state }, value)
+    }
+
+    /// Calls `f` with `args`, recording the event raising.
+    ///
+    /// The nesting depth is restored even if `f` panics.
+    pub(crate) fn run_event<Owner: DepType, ArgsType: DepEventArgs, R>(
+        state: &mut dyn State,
+        id: Owner::Id,
+        event: DepEvent<Owner, ArgsType>,
+        args: ArgsType,
+        f: impl FnOnce(&mut dyn State, ArgsType) -> R
+    ) -> R where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        let entered = Self::enter(state, |driven| Box::new(DepEventRecord {
+            owner_type: owner_type_id::<Owner>(),
+            owner: type_name::<Owner>(),
+            offset: event.offset(),
+            id: id.into_raw(),
+            args: args.detach_handled(),
+            driven,
+            replay: replay_event::<Owner, ArgsType>,
+        }));
+        if !entered { return f(state, args); }
+        let guard = RecorderGuard { state, driven: false };
+        // SAFETY: `state` is not used after the guard creation.
+        f(unsafe { &mut *guard.state }, args)
+    }
+
+    /// Calls `f`, marking mutations it makes outside of recorded calls as driven.
+    ///
+    /// Used by binding targets and evaluation entry points, which are not recorded themselves.
+    pub(crate) fn run_driven<R>(state: &mut dyn State, f: impl FnOnce(&mut dyn State) -> R) -> R {
+        let recorder = if let Some(recorder) = state.get_mut_raw(TypeId::of::<Recorder>()) {
+            recorder.downcast_mut::<Recorder>().unwrap()
+        } else {
+            return f(state);
+        };
+        recorder.driven += 1;
+        let guard = RecorderGuard { state, driven: true };
+        // SAFETY: `state` is not used after the guard creation.
+        f(unsafe { &mut *guard.state })
+    }
+}
+
+/// The [`RecordFormat::write`] method error.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum RecordWriteError {
+    /// There is no property or event with such owner and offset registered in the format.
+    UnknownMember { index: usize, owner: &'static str, offset: usize },
+    /// The value text contains a line break.
+    MultilineValue { index: usize, name: &'static str },
+}
+
+impl Display for RecordWriteError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RecordWriteError::UnknownMember { index, owner, offset } =>
+                write!(f, "record {}: unknown member {}+{}", index, owner, offset),
+            RecordWriteError::MultilineValue { index, name } =>
+                write!(f, "record {}: '{}' value contains a line break", index, name),
+        }
+    }
+}
+
+/// The [`RecordFormat::parse`] method error.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum RecordParseError {
+    /// A line does not have the `[driven] set|unset|raise id name [value]` form.
+    InvalidRecord { line: usize, text: String },
+    /// An id does not have the `index:guard` form.
+    InvalidId { line: usize, id: String },
+    /// There is no property or event with such name registered in the format.
+    UnknownMember { line: usize, name: String },
+    /// The value cannot be parsed as a property type value or an event args type value.
+    InvalidValue { line: usize, name: String, value: String, expected: &'static str },
+}
+
+impl Display for RecordParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RecordParseError::InvalidRecord { line, text } =>
+                write!(f, "line {}: invalid record '{}', expected '[driven] set|unset|raise id name [value]'", line, text),
+            RecordParseError::InvalidId { line, id } =>
+                write!(f, "line {}: invalid id '{}', expected 'index:guard'", line, id),
+            RecordParseError::UnknownMember { line, name } =>
+                write!(f, "line {}: unknown member '{}'", line, name),
+            RecordParseError::InvalidValue { line, name, value, expected } =>
+                write!(f, "line {}: invalid '{}' value '{}', expected {}", line, name, value, expected),
+        }
+    }
+}
+
+trait AnyRecordMember: Debug {
+    fn owner_type(&self) -> TypeId;
+    fn offset(&self) -> usize;
+    fn value_type(&self) -> &'static str;
+    fn write_value(&self, value: &dyn Any) -> String;
+    fn parse(&self, id: RawId, value: Option<&str>, driven: bool) -> Option<Box<dyn Record>>;
+}
+
+#[derive(Educe)]
+#[educe(Debug)]
+struct PropMember<Owner: DepType, PropType: Convenient> {
+    prop: DepProp<Owner, PropType>,
+}
+
+impl<Owner: DepType + 'static, PropType: Convenient + Display + FromStr> AnyRecordMember
+    for PropMember<Owner, PropType> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+
+    fn owner_type(&self) -> TypeId { owner_type_id::<Owner>() }
+
+    fn offset(&self) -> usize { self.prop.offset() }
+
+    fn value_type(&self) -> &'static str { type_name::<PropType>() }
+
+    fn write_value(&self, value: &dyn Any) -> String {
+        value.downcast_ref::<PropType>().unwrap().to_string()
+    }
+
+    fn parse(&self, id: RawId, value: Option<&str>, driven: bool) -> Option<Box<dyn Record>> {
+        let value = if let Some(value) = value { Some(value.parse().ok()?) } else { None };
+        Some(Box::new(DepPropRecord {
+            owner_type: owner_type_id::<Owner>(),
+            owner: type_name::<Owner>(),
+            offset: self.prop.offset(),
+            id,
+            value,
+            driven,
+            replay: replay_prop::<Owner, PropType>,
+        }))
+    }
+}
+
+#[derive(Educe)]
+#[educe(Debug)]
+struct EventMember<Owner: DepType, ArgsType: DepEventArgs> {
+    event: DepEvent<Owner, ArgsType>,
+}
+
+impl<Owner: DepType + 'static, ArgsType: DepEventArgs + Display + FromStr> AnyRecordMember
+    for EventMember<Owner, ArgsType> where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+
+    fn owner_type(&self) -> TypeId { owner_type_id::<Owner>() }
+
+    fn offset(&self) -> usize { self.event.offset() }
+
+    fn value_type(&self) -> &'static str { type_name::<ArgsType>() }
+
+    fn write_value(&self, value: &dyn Any) -> String {
+        value.downcast_ref::<ArgsType>().unwrap().to_string()
+    }
+
+    fn parse(&self, id: RawId, value: Option<&str>, driven: bool) -> Option<Box<dyn Record>> {
+        Some(Box::new(DepEventRecord {
+            owner_type: owner_type_id::<Owner>(),
+            owner: type_name::<Owner>(),
+            offset: self.event.offset(),
+            id,
+            args: value?.parse().ok()?,
+            driven,
+            replay: replay_event::<Owner, ArgsType>,
+        }))
+    }
+}
+
+/// A dictionary mapping names to dependency properties and events,
+/// allowing to write a log to text and parse it back.
+///
+/// The format has a line per record:
+///
+/// ```text
+/// set 0:8364 base_weight 5
+/// unset 0:8364 cursed
+/// raise 1:2271 used 1
+/// driven set 1:2271 name from timer
+/// ```
+///
+/// Values are written with the [`Display`] implementation of the property (event args) type,
+/// and parsed with the [`FromStr`] one. A value is the rest of the line after the space
+/// following the name, taken verbatim: it is neither quoted nor escaped, so leading and trailing
+/// spaces are kept, and values containing line breaks cannot be written
+/// (see [`RecordWriteError::MultilineValue`]).
+///
+/// Written members are looked up by the owner type id and the member offset,
+/// type names are used in error messages only.
+///
+/// # Examples
+///
+/// ```ignore
+/// let format = RecordFormat::new()
+///     .prop("base_weight", ItemProps::BASE_WEIGHT)
+///     .event("used", ItemProps::USED);
+/// let text = format.write(recorder.records()).unwrap();
+/// let records = format.parse(&text).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RecordFormat {
+    props: Vec<(&'static str, Box<dyn AnyRecordMember>)>,
+    events: Vec<(&'static str, Box<dyn AnyRecordMember>)>,
+}
+
+impl const Default for RecordFormat {
+    fn default() -> Self { RecordFormat::new() }
+}
+
+impl RecordFormat {
+    pub const fn new() -> Self { RecordFormat { props: Vec::new(), events: Vec::new() } }
+
+    /// Registers the property under the specified name.
+    ///
+    /// Panics if the name is already registered.
+    pub fn prop<Owner: DepType + 'static, PropType: Convenient + Display + FromStr>(
+        mut self,
+        name: &'static str,
+        prop: DepProp<Owner, PropType>
+    ) -> Self where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        assert!(self.props.iter().all(|x| x.0 != name), "duplicate record property name '{}'", name);
+        self.props.push((name, Box::new(PropMember { prop })));
+        self
+    }
+
+    /// Registers the event under the specified name.
+    ///
+    /// Panics if the name is already registered.
+    pub fn event<Owner: DepType + 'static, ArgsType: DepEventArgs + Display + FromStr>(
+        mut self,
+        name: &'static str,
+        event: DepEvent<Owner, ArgsType>
+    ) -> Self where Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        assert!(self.events.iter().all(|x| x.0 != name), "duplicate record event name '{}'", name);
+        self.events.push((name, Box::new(EventMember { event })));
+        self
+    }
+
+    fn members(&self, kind: RecordKind) -> &[(&'static str, Box<dyn AnyRecordMember>)] {
+        if kind == RecordKind::Raise { &self.events } else { &self.props }
+    }
+
+    pub fn write(&self, records: &[Box<dyn Record>]) -> Result<String, RecordWriteError> {
+        let mut text = String::new();
+        for (index, record) in records.iter().enumerate() {
+            let &(name, ref member) = self.members(record.kind()).iter()
+                .find(|x| x.1.owner_type() == record.owner_type() && x.1.offset() == record.offset())
+                .ok_or(RecordWriteError::UnknownMember { index, owner: record.owner(), offset: record.offset() })?;
+            if record.is_driven() { text.push_str("driven "); }
+            let verb = match record.kind() {
+                RecordKind::Set => "set",
+                RecordKind::Unset => "unset",
+                RecordKind::Raise => "raise",
+            };
+            let id = record.id();
+            write!(text, "{} {}:{} {}", verb, id.0, id.1, name).unwrap();
+            if let Some(value) = record.value() {
+                let value = member.write_value(value);
+                if value.contains(&['\n', '\r']) {
+                    return Err(RecordWriteError::MultilineValue { index, name });
+                }
+                text.push(' ');
+                text.push_str(&value);
+            }
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    pub fn parse(&self, text: &str) -> Result<Vec<Box<dyn Record>>, RecordParseError> {
+        let mut records = Vec::new();
+        for (index, record) in text.lines().enumerate() {
+            let line = index + 1;
+            if record.trim().is_empty() { continue; }
+            let invalid_record = || RecordParseError::InvalidRecord { line, text: record.to_string() };
+            let (driven, rest) = if let Some(rest) = record.strip_prefix("driven ") {
+                (true, rest)
+            } else {
+                (false, record)
+            };
+            let mut parts = rest.splitn(4, ' ');
+            let kind = match parts.next() {
+                Some("set") => RecordKind::Set,
+                Some("unset") => RecordKind::Unset,
+                Some("raise") => RecordKind::Raise,
+                _ => return Err(invalid_record()),
+            };
+            let id = parts.next().ok_or_else(invalid_record)?;
+            let name = parts.next().ok_or_else(invalid_record)?;
+            let value = parts.next();
+            if (kind == RecordKind::Unset) != value.is_none() { return Err(invalid_record()); }
+            let id = parse_id(id).ok_or_else(|| RecordParseError::InvalidId { line, id: id.to_string() })?;
+            let (_, member) = self.members(kind).iter().find(|x| x.0 == name).ok_or_else(|| {
+                RecordParseError::UnknownMember { line, name: name.to_string() }
+            })?;
+            let record = member.parse(id, value, driven).ok_or_else(|| RecordParseError::InvalidValue {
+                line,
+                name: name.to_string(),
+                value: value.unwrap_or_default().to_string(),
+                expected: member.value_type()
+            })?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
+fn parse_id(id: &str) -> Option<RawId> {
+    let (index, guard) = id.split_once(':')?;
+    Some((usize::from_str(index).ok()?, NonZeroUsize::from_str(guard).ok()?))
+}
@@ -0,0 +1,166 @@
+//! A textual [`Style`] description.
+//!
+//! The format is a list of `name: value` declarations separated by semicolons:
+//!
+//! ```text
+//! base_weight: 5.0;
+//! cursed: true;
+//! ```
+//!
+//! Property names are mapped to dependency properties with a [`StyleParser`],
+//! and values are parsed with the [`FromStr`] implementation of the property type.
+
+use crate::{Convenient, DepObj, DepProp, DepType, Style};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::str::FromStr;
+use educe::Educe;
+
+/// The [`StyleParser::parse`] method error.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum StyleParseError {
+    /// A declaration does not have the `name: value` form.
+    InvalidDeclaration { line: usize, text: String },
+    /// There is no property with such name registered in the parser.
+    UnknownProp { line: usize, name: String },
+    /// The property is declared more than once.
+    DuplicateProp { line: usize, name: String },
+    /// The value cannot be parsed as a property type value.
+    /// The `reason` is the [`FromStr::Err`] error text.
+    InvalidValue { line: usize, name: String, value: String, expected: &'static str, reason: String },
+    /// The declarations count exceeds the style capacity.
+    TooManyProps { line: usize, capacity: usize },
+}
+
+impl Display for StyleParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            StyleParseError::InvalidDeclaration { line, text } =>
+                write!(f, "line {}: invalid declaration '{}', expected 'name: value'", line, text),
+            StyleParseError::UnknownProp { line, name } =>
+                write!(f, "line {}: unknown property '{}'", line, name),
+            StyleParseError::DuplicateProp { line, name } =>
+                write!(f, "line {}: duplicate property '{}'", line, name),
+            StyleParseError::InvalidValue { line, name, value, expected, reason } =>
+                write!(f, "line {}: invalid '{}' property value '{}', expected {}: {}", line, name, value, expected, reason),
+            StyleParseError::TooManyProps { line, capacity } =>
+                write!(f, "line {}: too many properties, style capacity is {}", line, capacity),
+        }
+    }
+}
+
+trait AnyStyleProp<Owner: DepType + 'static>: Debug {
+    fn value_type(&self) -> &'static str;
+    fn contained_in(&self, style: &Style<Owner>) -> bool;
+    fn parse_and_insert(&self, style: &mut Style<Owner>, value: &str) -> Result<(), String>;
+}
+
+#[derive(Educe)]
+#[educe(Debug)]
+struct StyleProp<Owner: DepType, PropType: Convenient> {
+    prop: DepProp<Owner, PropType>,
+}
+
+impl<Owner: DepType + 'static, PropType: Convenient + FromStr> AnyStyleProp<Owner> for StyleProp<Owner, PropType> where
+    PropType::Err: Display, Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+
+    fn value_type(&self) -> &'static str { type_name::<PropType>() }
+
+    fn contained_in(&self, style: &Style<Owner>) -> bool { style.contains_prop(self.prop) }
+
+    fn parse_and_insert(&self, style: &mut Style<Owner>, value: &str) -> Result<(), String> {
+        let value = value.parse().map_err(|e: PropType::Err| e.to_string())?;
+        style.insert(self.prop, value);
+        Ok(())
+    }
+}
+
+/// A dictionary mapping property names to dependency properties,
+/// allowing to build a [`Style`] from its textual description.
+///
+/// Properties are registered explicitly rather than generated by [`dep_type`](crate::dep_type):
+/// a dependency type can have properties of types not implementing [`FromStr`],
+/// which cannot be skipped without specialization, and the textual names are a part of
+/// the style format, so they can be chosen independently of the field names.
+///
+/// # Examples
+///
+/// ```ignore
+/// let parser = StyleParser::new()
+///     .prop("base_weight", ItemProps::BASE_WEIGHT)
+///     .prop("cursed", ItemProps::CURSED);
+/// let style = parser.parse("base_weight: 5.0; cursed: true;").unwrap();
+/// ```
+#[derive(Educe)]
+#[educe(Debug)]
+pub struct StyleParser<Owner: DepType + 'static> {
+    props: Vec<(&'static str, Box<dyn AnyStyleProp<Owner>>)>,
+}
+
+impl<Owner: DepType> const Default for StyleParser<Owner> {
+    fn default() -> Self { StyleParser::new() }
+}
+
+impl<Owner: DepType + 'static> StyleParser<Owner> {
+    pub const fn new() -> Self { StyleParser { props: Vec::new() } }
+
+    /// Registers the property under the specified name.
+    ///
+    /// Panics if the name is already registered.
+    pub fn prop<PropType: Convenient + FromStr>(
+        mut self,
+        name: &'static str,
+        prop: DepProp<Owner, PropType>
+    ) -> Self where PropType::Err: Display, Owner::Id: DepObj<Owner::DepObjKey, Owner> {
+        assert!(self.props.iter().all(|x| x.0 != name), "duplicate style property name '{}'", name);
+        self.props.push((name, Box::new(StyleProp { prop })));
+        self
+    }
+
+    pub fn parse(&self, text: &str) -> Result<Style<Owner>, StyleParseError> {
+        let mut style = Style::new();
+        self.parse_into(&mut style, text)?;
+        Ok(style)
+    }
+
+    /// Parses `text` and inserts the declared values into an existing style.
+    ///
+    /// On error, the style can contain some of the values declared before the failed declaration.
+    pub fn parse_into(&self, style: &mut Style<Owner>, text: &str) -> Result<(), StyleParseError> {
+        let mut declared = Vec::new();
+        let mut line = 1;
+        for declaration in text.split(';') {
+            let leading_space = &declaration[.. declaration.len() - declaration.trim_start().len()];
+            let declaration_line = line + leading_space.matches('\n').count();
+            line += declaration.matches('\n').count();
+            let declaration = declaration.trim();
+            if declaration.is_empty() { continue; }
+            let (name, value) = declaration.split_once(':').ok_or_else(|| StyleParseError::InvalidDeclaration {
+                line: declaration_line,
+                text: declaration.to_string()
+            })?;
+            let (name, value) = (name.trim(), value.trim());
+            let &(name, ref prop) = self.props.iter().find(|x| x.0 == name).ok_or_else(|| {
+                StyleParseError::UnknownProp { line: declaration_line, name: name.to_string() }
+            })?;
+            if declared.contains(&name) {
+                return Err(StyleParseError::DuplicateProp { line: declaration_line, name: name.to_string() });
+            }
+            declared.push(name);
+            if style.len() == style.capacity() && !prop.contained_in(style) {
+                return Err(StyleParseError::TooManyProps { line: declaration_line, capacity: style.capacity() });
+            }
+            prop.parse_and_insert(style, value).map_err(|reason| StyleParseError::InvalidValue {
+                line: declaration_line,
+                name: name.to_string(),
+                value: value.to_string(),
+                expected: prop.value_type(),
+                reason
+            })?;
+        }
+        Ok(())
+    }
+}